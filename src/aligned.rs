@@ -0,0 +1,216 @@
+//! Over-aligned Unions.
+//!
+//! [`Aligned<U, A>`] is a [`TypeUnion`] with the same types as `U`, whose Union is aligned to at
+//! least the alignment of `A`. A union-vector of an `Aligned` Union therefore always has a buffer
+//! with that alignment, whatever type it currently holds.
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use pod::PodUnion;
+use select::{
+    Contains, EnumUnion, MemberInfo, MemberVisitor, ReprC, Select, Selector, SubsetOf, TypeSelect,
+    TypeUnion,
+};
+
+/// A zero-sized type, of which only the alignment is used. See [`Aligned`].
+pub trait Alignment {}
+
+macro_rules! alignment {
+    ($($name:ident = $align:tt),*) => ($(
+        #[doc = concat!("A zero-sized type with an alignment of ", stringify!($align), " bytes.")]
+        #[repr(align($align))]
+        #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+        pub struct $name;
+
+        impl Alignment for $name {}
+    )*);
+}
+
+alignment!(Align16 = 16, Align32 = 32, Align64 = 64, Align128 = 128);
+
+/// A [`TypeUnion`] with the types of `U`, aligned to at least the alignment of `A`.
+///
+/// # Examples
+/// ```
+/// extern crate unioncollections;
+///
+/// use unioncollections::aligned::{Align64, Aligned};
+/// use unioncollections::collections::unionvec::UnionVec;
+/// use unioncollections::index::Type2;
+///
+/// let mut union_vec = UnionVec::<[f32; 8], Aligned<([f32; 8], [i32; 8]), Align64>>::new();
+/// union_vec.push([1.5; 8]);
+///
+/// let union_vec = union_vec.map::<Type2, _>(|floats| floats.map(|f| f as i32));
+///
+/// assert_eq!(union_vec.as_ptr() as usize % 64, 0);
+/// assert_eq!(union_vec.into_vec(), vec![[1; 8]]);
+/// ```
+pub struct Aligned<U, A> {
+    marker: PhantomData<(U, A)>,
+}
+
+/// The Union of [`Aligned<U, A>`].
+#[repr(C)]
+pub struct AlignedUnion<U: TypeUnion, A> {
+    union: U::Union,
+    _align: [A; 0],
+}
+
+impl<U: TypeUnion, A> AlignedUnion<U, A> {
+    #[inline]
+    fn new(union: U::Union) -> Self {
+        Self { union, _align: [] }
+    }
+}
+
+/// Forwards a visitor over the types of `U`, to a visitor over the types of `Aligned<U, A>`.
+struct Forward<'a, V: 'a, A>(&'a mut V, PhantomData<A>);
+
+impl<'a, U, A, V> MemberVisitor<U> for Forward<'a, V, A>
+where
+    U: TypeUnion,
+    A: Alignment,
+    V: MemberVisitor<Aligned<U, A>>,
+{
+    #[inline]
+    fn visit<S>(&mut self, info: MemberInfo)
+    where
+        S: Selector,
+        U: Select<S>,
+    {
+        self.0.visit::<S>(info)
+    }
+}
+
+impl<U: TypeUnion, A: Alignment> TypeUnion for Aligned<U, A> {
+    type Union = AlignedUnion<U, A>;
+
+    const MEMBER_LAYOUTS: &'static [::std::alloc::Layout] = U::MEMBER_LAYOUTS;
+
+    #[inline]
+    fn contains<T: 'static>() -> bool
+    where
+        Self: 'static,
+    {
+        U::contains::<T>()
+    }
+
+    #[inline]
+    fn member_type_ids() -> Vec<TypeId>
+    where
+        Self: 'static,
+    {
+        U::member_type_ids()
+    }
+
+    #[inline]
+    fn member_type_names() -> Vec<&'static str> {
+        U::member_type_names()
+    }
+
+    #[inline]
+    unsafe fn drop_member(index: usize, union: *mut Self::Union) {
+        U::drop_member(index, union as *mut U::Union)
+    }
+
+    #[inline]
+    fn for_each_member<V: MemberVisitor<Self>>(visitor: &mut V)
+    where
+        Self: 'static,
+    {
+        U::for_each_member(&mut Forward::<V, A>(visitor, PhantomData))
+    }
+}
+
+unsafe impl<U: TypeUnion, A: Alignment> TypeSelect<Aligned<U, A>> for AlignedUnion<U, A> {}
+
+impl<U, A, S> Select<S> for Aligned<U, A>
+where
+    U: Select<S>,
+    S: Selector,
+{
+    type Output = <U as Select<S>>::Output;
+}
+
+impl<U, A, T, S> Contains<T, S> for Aligned<U, A>
+where
+    U: Contains<T, S>,
+    S: Selector,
+{
+}
+
+impl<U, A, U2, I> SubsetOf<U2, I> for Aligned<U, A> where U: SubsetOf<U2, I> {}
+
+unsafe impl<U: ReprC, A: Alignment> ReprC for Aligned<U, A> {}
+
+unsafe impl<U: PodUnion, A: Alignment> PodUnion for Aligned<U, A> {}
+
+impl<U: EnumUnion, A: Alignment> EnumUnion for Aligned<U, A> {
+    type Enum = U::Enum;
+
+    #[inline]
+    fn enum_index(e: &Self::Enum) -> usize {
+        U::enum_index(e)
+    }
+
+    #[inline]
+    fn enum_into_union(e: Self::Enum) -> Self::Union {
+        AlignedUnion::new(U::enum_into_union(e))
+    }
+
+    #[inline]
+    unsafe fn union_into_enum(index: usize, union: Self::Union) -> Self::Enum {
+        U::union_into_enum(index, union.union)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collections::unionvec::UnionVec;
+    use index::{Type1, Type2};
+
+    type Lanes = Aligned<([f32; 8], [i32; 8]), Align32>;
+
+    fn is_aligned<T, U: TypeUnion>(union_vec: &UnionVec<T, U>, align: usize) -> bool {
+        (union_vec.as_ptr() as usize).is_multiple_of(align)
+    }
+
+    #[test]
+    fn test_aligned_across_stages() {
+        assert_eq!(Lanes::ALIGN, 32);
+        assert_eq!(Lanes::SIZE, 32);
+
+        let mut union_vec = UnionVec::<[f32; 8], Lanes>::with_capacity(1);
+        union_vec.push([0.5; 8]);
+        union_vec.reserve(100);
+        assert!(is_aligned(&union_vec, 32));
+
+        let union_vec = union_vec.map::<Type2, _>(|floats| floats.map(|f| (f * 4.0) as i32));
+        assert!(is_aligned(&union_vec, 32));
+
+        let mut union_vec = union_vec.change_to::<Type1>();
+        union_vec.push([1.0; 8]);
+        assert!(is_aligned(&union_vec, 32));
+
+        assert_eq!(union_vec.into_vec(), vec![[1.0; 8]]);
+    }
+
+    #[test]
+    fn test_aligned_widen_and_into_vec() {
+        let mut union_vec = UnionVec::<u8, (u8, u16)>::new();
+
+        for n in 0..10 {
+            union_vec.push(n);
+        }
+
+        let union_vec = union_vec.widen::<Aligned<(u8, u16), Align64>, _>();
+        assert_eq!(union_vec.memory_report().union_size, 64);
+        assert!(is_aligned(&union_vec, 64));
+
+        let union_vec = union_vec.map::<Type2, _>(u16::from);
+        assert_eq!(union_vec.into_vec(), (0..10).collect::<Vec<u16>>());
+    }
+}
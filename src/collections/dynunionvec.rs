@@ -0,0 +1,321 @@
+//! A union-vector whose current type is only known at runtime.
+
+use std::error;
+use std::fmt;
+use std::mem;
+use std::ptr;
+
+use collections::unionvec::UnionVec;
+use select::{Contains, EnumUnion, Select, Selector, TypeUnion};
+
+/// The error returned when an index does not select a type of the Union.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct IndexOutOfRange {
+    /// The index that was given.
+    pub index: usize,
+
+    /// The number of types of the Union.
+    pub members: usize,
+}
+
+impl fmt::Display for IndexOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "index {} is out of range for a union of {} types",
+            self.index, self.members
+        )
+    }
+}
+
+impl error::Error for IndexOutOfRange {}
+
+/// A DynUnionVec is a [`UnionVec`] that keeps track of its current type at runtime, as the index
+/// of that type in `U`.
+///
+/// This allows the current type to be chosen at runtime, with
+/// [`DynUnionVec::change_to_index`]. To access the elements, the union-vector has to be converted
+/// back into a [`UnionVec`] with [`DynUnionVec::downcast`].
+///
+/// # Examples
+/// ```
+/// extern crate unioncollections;
+///
+/// use unioncollections::collections::dynunionvec::DynUnionVec;
+/// use unioncollections::collections::unionvec::UnionVec;
+/// use unioncollections::index::{Type1, Type2};
+///
+/// let mut union_vec = UnionVec::<u32, (u32, String)>::new();
+/// union_vec.push(10);
+///
+/// let mut dyn_vec = DynUnionVec::from(union_vec);
+/// assert_eq!(dyn_vec.index(), 0);
+///
+/// dyn_vec.change_to_index(1).unwrap();
+/// assert!(dyn_vec.is_empty());
+///
+/// let dyn_vec = dyn_vec.downcast::<Type1>().unwrap_err();
+/// let mut union_vec = dyn_vec.downcast::<Type2>().unwrap();
+///
+/// union_vec.push(String::from("ten"));
+/// assert_eq!(union_vec.pop(), Some(String::from("ten")));
+/// ```
+pub struct DynUnionVec<U: TypeUnion> {
+    data: Vec<U::Union>,
+    index: usize,
+}
+
+impl<U: TypeUnion> DynUnionVec<U> {
+    /// Constructs a new, empty `DynUnionVec<U>`, holding the `index`th type of `U`.
+    #[inline]
+    pub fn with_index(index: usize) -> Result<Self, IndexOutOfRange> {
+        Self::check_index(index)?;
+
+        Ok(Self {
+            data: Vec::new(),
+            index,
+        })
+    }
+
+    /// Converts a [`UnionVec`] into a `DynUnionVec<U>`, holding the type of `U` selected by `S`.
+    ///
+    /// Unlike the conversion with `From`, the index is known at compiletime, so this works for
+    /// Unions of borrowed types, and selects the right type when it occurs more than once in `U`.
+    #[inline]
+    pub fn from_union_vec<T, S>(union_vec: UnionVec<T, U>) -> Self
+    where
+        S: Selector,
+        U: Contains<T, S>,
+    {
+        Self {
+            data: union_vec.take_data(),
+            index: S::INDEX,
+        }
+    }
+
+    #[inline]
+    fn check_index(index: usize) -> Result<(), IndexOutOfRange> {
+        let members = U::MEMBER_LAYOUTS.len();
+
+        if index < members {
+            Ok(())
+        } else {
+            Err(IndexOutOfRange { index, members })
+        }
+    }
+
+    /// Returns the index of the current type in `U`.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the name of the current type.
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        U::member_type_names()[self.index]
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    /// Drops all elements, keeping the capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        let len = self.data.len();
+
+        unsafe {
+            self.data.set_len(0);
+
+            let ptr = self.data.as_mut_ptr();
+
+            for i in 0..len {
+                U::drop_member(self.index, ptr.add(i));
+            }
+        }
+    }
+
+    /// Clears the union-vector, and changes the current type to the `index`th type of `U`.
+    /// The capacity is kept.
+    #[inline]
+    pub fn change_to_index(&mut self, index: usize) -> Result<(), IndexOutOfRange> {
+        Self::check_index(index)?;
+
+        self.clear();
+        self.index = index;
+
+        Ok(())
+    }
+
+    /// Converts the union-vector into a [`UnionVec`] holding the type selected by `S`.
+    /// When that is not the current type, the union-vector is returned as is.
+    #[inline]
+    pub fn downcast<S>(self) -> Result<UnionVec<<U as Select<S>>::Output, U>, Self>
+    where
+        S: Selector,
+        U: Select<S>,
+    {
+        if S::INDEX != self.index {
+            return Err(self);
+        }
+
+        Ok(unsafe { UnionVec::from_data_unchecked(self.into_data()) })
+    }
+
+    /// Converts the union-vector into a Vec of enums, see [`UnionVec::into_enum_vec`].
+    #[inline]
+    pub fn into_enum_vec(self) -> Vec<U::Enum>
+    where
+        U: EnumUnion,
+    {
+        let index = self.index;
+
+        self.into_data()
+            .into_iter()
+            .map(|union| unsafe { U::union_into_enum(index, union) })
+            .collect()
+    }
+
+    #[inline]
+    fn into_data(self) -> Vec<U::Union> {
+        let data = unsafe { ptr::read(&self.data) };
+        mem::forget(self);
+        data
+    }
+}
+
+/// Finds the index of `T` at runtime, see [`DynUnionVec::from_union_vec`] to give it at
+/// compiletime.
+///
+/// # Panic
+///
+/// When `T` is not one of the types of `U`, this function will panic. When `T` occurs more than
+/// once, the first index is used.
+impl<T: 'static, U: TypeUnion + 'static> From<UnionVec<T, U>> for DynUnionVec<U> {
+    #[inline]
+    fn from(union_vec: UnionVec<T, U>) -> Self {
+        let index = U::index_of::<T>().expect("`T` is not a type of the union");
+
+        Self {
+            data: union_vec.take_data(),
+            index,
+        }
+    }
+}
+
+/// The elements can not be formatted, only the current type and the length are shown.
+impl<U: TypeUnion> fmt::Debug for DynUnionVec<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DynUnionVec")
+            .field("index", &self.index)
+            .field("type_name", &self.type_name())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<U: TypeUnion> Drop for DynUnionVec<U> {
+    #[inline]
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use index::{Type2, Type3};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_dyn_union_vec_drops_current_type() {
+        let rc = Rc::new(());
+
+        let mut union_vec = UnionVec::<Rc<()>, (u8, String, Rc<()>)>::new();
+        union_vec.push(Rc::clone(&rc));
+        union_vec.push(Rc::clone(&rc));
+
+        let mut dyn_vec = DynUnionVec::from(union_vec);
+        assert_eq!(dyn_vec.index(), 2);
+        assert_eq!(Rc::strong_count(&rc), 3);
+
+        dyn_vec.change_to_index(1).unwrap();
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let mut union_vec = dyn_vec.downcast::<Type2>().ok().unwrap();
+        union_vec.push(String::from("dropped"));
+
+        let mut dyn_vec = DynUnionVec::from(union_vec);
+        dyn_vec.change_to_index(2).unwrap();
+
+        let mut union_vec = dyn_vec.downcast::<Type3>().ok().unwrap();
+        union_vec.push(Rc::clone(&rc));
+        union_vec.push(Rc::clone(&rc));
+
+        let dyn_vec = DynUnionVec::from(union_vec);
+        assert_eq!(Rc::strong_count(&rc), 3);
+
+        drop(dyn_vec);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn test_dyn_union_vec_from_union_vec() {
+        let text = String::from("borrowed");
+
+        let mut union_vec = UnionVec::<&str, (&str, u8, &str)>::new();
+        union_vec.push(&text);
+
+        let dyn_vec = DynUnionVec::from_union_vec::<_, Type3>(union_vec);
+        assert_eq!(dyn_vec.index(), 2);
+
+        let mut union_vec = dyn_vec.downcast::<Type3>().ok().unwrap();
+        assert_eq!(union_vec.pop(), Some("borrowed"));
+    }
+
+    #[test]
+    fn test_dyn_union_vec_index_out_of_range() {
+        let mut dyn_vec = DynUnionVec::<(u8, u16, u32)>::with_index(2).unwrap();
+
+        assert_eq!(
+            dyn_vec.change_to_index(3),
+            Err(IndexOutOfRange {
+                index: 3,
+                members: 3
+            })
+        );
+        assert_eq!(dyn_vec.type_name(), "u32");
+        assert!(dyn_vec.downcast::<Type3>().is_ok());
+    }
+
+    #[test]
+    fn test_dyn_union_vec_into_enum_vec() {
+        use select::SelectHandle;
+        use union::OneOf3;
+
+        let mut union_vec = UnionVec::<String, (u8, String, u32)>::new();
+        union_vec.push(String::from("one"));
+
+        let dyn_vec = DynUnionVec::from(union_vec);
+
+        assert_eq!(
+            dyn_vec.into_enum_vec(),
+            vec![OneOf3::Type2(String::from("one"))]
+        );
+
+        let handle = SelectHandle::<u32, (u8, String, u32)>::from(3);
+        assert_eq!(handle.into_enum(), OneOf3::Type3(3));
+    }
+}
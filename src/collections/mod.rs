@@ -1 +1,75 @@
+use select::{Select, Selector, TypeUnion};
+
+pub mod dynunionvec;
+pub mod pipeline;
+pub mod pool;
 pub mod unionvec;
+
+/// A collection that holds elements of one of the types of `U` at a time, and that can change
+/// between those types.
+///
+/// This allows functions to be written once for every such collection.
+///
+/// # Examples
+/// ```
+/// extern crate unioncollections;
+///
+/// use unioncollections::collections::unionvec::UnionVec;
+/// use unioncollections::collections::UnionCollection;
+/// use unioncollections::index::Type2;
+///
+/// fn parse<C>(lines: C) -> C::Rebind<Type2>
+/// where
+///     C: UnionCollection<String, (String, u64)>,
+/// {
+///     lines.filter_map::<Type2, _>(|s| s.trim().parse().ok())
+/// }
+///
+/// let mut union_vec = UnionVec::<String, (String, u64)>::new();
+/// union_vec.push(String::from(" 10 "));
+/// union_vec.push(String::from("x"));
+///
+/// let mut union_vec = parse(union_vec);
+///
+/// assert_eq!(union_vec.len(), 1);
+/// assert_eq!(union_vec.pop(), Some(10));
+/// ```
+pub trait UnionCollection<T, U: TypeUnion>: Sized {
+    /// The same kind of collection, holding the type selected by `S`.
+    type Rebind<S>: UnionCollection<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>;
+
+    /// Returns the number of elements in the collection.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the collection holds no elements.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of elements the collection can hold without reallocating.
+    fn capacity(&self) -> usize;
+
+    /// Clears the collection, and changes it to hold the type selected by `S`.
+    fn change_to<S>(self) -> Self::Rebind<S>
+    where
+        S: Selector,
+        U: Select<S>;
+
+    /// Converts every element into the type selected by `S`.
+    fn map<S, F>(self, f: F) -> Self::Rebind<S>
+    where
+        S: Selector,
+        U: Select<S>,
+        F: Fn(T) -> <U as Select<S>>::Output;
+
+    /// Converts every element into the type selected by `S`, or discards it.
+    fn filter_map<S, F>(self, f: F) -> Self::Rebind<S>
+    where
+        S: Selector,
+        U: Select<S>,
+        F: Fn(T) -> Option<<U as Select<S>>::Output>;
+}
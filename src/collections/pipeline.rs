@@ -0,0 +1,207 @@
+use std::marker::PhantomData;
+
+use collections::unionvec::UnionVec;
+use select::{Select, Selected, Selector, TypeUnion};
+
+/// A chain of conversions on a [`UnionVec`], that is run in a single pass over the elements.
+///
+/// Every stage is composed with the stages before it, and the output of every stage is checked
+/// at compiletime to be one of the types of `U`. Only the output of the last stage is ever
+/// written back into the union-vector.
+///
+/// A pipeline is created with [`UnionVec::pipeline`].
+///
+/// # Examples
+/// ```
+/// extern crate unioncollections;
+///
+/// use unioncollections::collections::unionvec::UnionVec;
+/// use unioncollections::index::{Type2, Type3, Type4};
+///
+/// let mut union_vec = UnionVec::<&str, (&str, u64, i64, String)>::new();
+///
+/// for s in vec!["10", "20", "x", "40"] {
+///     union_vec.push(s);
+/// }
+///
+/// let mut union_vec = union_vec
+///     .pipeline()
+///     .filter_map::<Type2, _>(|s| s.parse().ok())
+///     .map::<Type3, _>(|n| n as i64 * -1)
+///     .map::<Type4, _>(|n| n.to_string())
+///     .run();
+///
+/// assert_eq!(union_vec.len(), 3);
+/// assert_eq!(union_vec.pop(), Some(String::from("-40")));
+/// ```
+pub struct Pipeline<T, U: TypeUnion, O, F> {
+    union_vec: UnionVec<T, U>,
+    f: F,
+    marker: PhantomData<O>,
+}
+
+/// The first stage of every pipeline, which passes the elements through.
+pub type Passthrough<T> = fn(T) -> Option<T>;
+
+/// A [`Pipeline`] of which the last stage outputs the type of `U` selected by `S`.
+pub type SelectedPipeline<T, U, S, F> = Pipeline<T, U, Selected<U, S>, F>;
+
+impl<T, U: TypeUnion> UnionVec<T, U> {
+    /// Starts a [`Pipeline`] of conversions on the union-vector.
+    #[inline]
+    pub fn pipeline(self) -> Pipeline<T, U, T, Passthrough<T>> {
+        Pipeline {
+            union_vec: self,
+            f: Some,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, U: TypeUnion, O, F> Pipeline<T, U, O, F>
+where
+    F: FnMut(T) -> Option<O>,
+{
+    /// Adds a stage that converts every element into the type selected by `S`.
+    #[inline]
+    pub fn map<S, G>(
+        self,
+        mut g: G,
+    ) -> SelectedPipeline<T, U, S, impl FnMut(T) -> Option<Selected<U, S>>>
+    where
+        S: Selector,
+        U: Select<S>,
+        G: FnMut(O) -> Selected<U, S>,
+    {
+        let mut f = self.f;
+
+        Pipeline {
+            union_vec: self.union_vec,
+            f: move |t| f(t).map(&mut g),
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds a stage that converts every element into the type selected by `S`, or discards it.
+    #[inline]
+    pub fn filter_map<S, G>(
+        self,
+        mut g: G,
+    ) -> SelectedPipeline<T, U, S, impl FnMut(T) -> Option<Selected<U, S>>>
+    where
+        S: Selector,
+        U: Select<S>,
+        G: FnMut(O) -> Option<Selected<U, S>>,
+    {
+        let mut f = self.f;
+
+        Pipeline {
+            union_vec: self.union_vec,
+            f: move |t| f(t).and_then(&mut g),
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds a stage that discards every element for which `predicate` returns `false`.
+    #[inline]
+    pub fn filter<P>(self, mut predicate: P) -> Pipeline<T, U, O, impl FnMut(T) -> Option<O>>
+    where
+        P: FnMut(&O) -> bool,
+    {
+        let mut f = self.f;
+
+        Pipeline {
+            union_vec: self.union_vec,
+            f: move |t| f(t).filter(&mut predicate),
+            marker: PhantomData,
+        }
+    }
+
+    /// Runs every stage on every element, in a single pass. The outputs of the last stage are
+    /// written back in place, like [`UnionVec::filter_map`] does.
+    ///
+    /// # Panic
+    ///
+    /// When any stage panics, the elements are dropped.
+    #[inline]
+    pub fn run(self) -> UnionVec<O, U> {
+        // `O` is either `T`, or the output of a stage, which is a type of `U`.
+        unsafe { self.union_vec.filter_map_unchecked(self.f) }
+    }
+
+    /// Runs the pipeline, and converts the result into a Vec, see [`UnionVec::into_vec`].
+    #[inline]
+    pub fn run_collect(self) -> Vec<O> {
+        self.run().into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use index::{Type2, Type3};
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_pipeline_single_pass() {
+        let log = RefCell::new(Vec::new());
+
+        let mut union_vec = UnionVec::<u32, (u32, u64, String)>::new();
+        union_vec.push(1);
+        union_vec.push(2);
+
+        let v = union_vec
+            .pipeline()
+            .map::<Type2, _>(|n| {
+                log.borrow_mut().push(("first", n));
+                n as u64
+            })
+            .map::<Type3, _>(|n| {
+                log.borrow_mut().push(("second", n as u32));
+                n.to_string()
+            })
+            .run_collect();
+
+        assert_eq!(v, vec![String::from("1"), String::from("2")]);
+        assert_eq!(
+            *log.borrow(),
+            vec![("first", 1), ("second", 1), ("first", 2), ("second", 2)]
+        );
+    }
+
+    #[test]
+    fn test_pipeline_filter() {
+        let mut union_vec = UnionVec::<u32, (u32, u64)>::new();
+
+        for n in 0..10 {
+            union_vec.push(n);
+        }
+
+        let v = union_vec
+            .pipeline()
+            .filter(|n| n % 2 == 1)
+            .map::<Type2, _>(|n| n as u64 * 10)
+            .run_collect();
+
+        assert_eq!(v, vec![10, 30, 50, 70, 90]);
+    }
+
+    #[test]
+    fn test_pipeline_borrowed() {
+        let text = String::from("1 2 x 4");
+
+        let mut union_vec = UnionVec::<&str, (&str, u64)>::new();
+
+        for s in text.split(' ') {
+            union_vec.push(s);
+        }
+
+        let v = union_vec
+            .pipeline()
+            .filter(|s| !s.is_empty())
+            .filter_map::<Type2, _>(|s| s.parse().ok())
+            .run_collect();
+
+        assert_eq!(v, vec![1, 2, 4]);
+    }
+}
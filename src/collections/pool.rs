@@ -0,0 +1,234 @@
+use std::mem;
+
+use collections::unionvec::UnionVec;
+use select::{Select, Selector, TypeUnion};
+
+/// Statistics of a [`UnionVecPool`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PoolStats {
+    /// The number of union-vectors handed out, that reused a retained allocation.
+    pub hits: usize,
+
+    /// The number of union-vectors handed out, for which no retained allocation was available.
+    pub misses: usize,
+
+    /// The number of union-vectors given back, whose allocation is retained.
+    pub retained: usize,
+
+    /// The number of union-vectors given back, whose allocation is freed.
+    pub discarded: usize,
+}
+
+/// A pool of union-vector allocations.
+///
+/// Any union-vector over `U` can be taken from the pool, as any of the types of `U`, and given
+/// back to it as whatever type it holds at that point. Given back union-vectors are cleared, and
+/// their allocation is reused for the next union-vector taken out.
+///
+/// # Examples
+/// ```
+/// extern crate unioncollections;
+///
+/// use unioncollections::collections::pool::UnionVecPool;
+/// use unioncollections::index::{Type1, Type2};
+///
+/// let mut pool = UnionVecPool::<(String, u64)>::new();
+///
+/// let mut strings = pool.take_with_capacity::<Type1>(16);
+/// strings.push(String::from("10"));
+///
+/// let numbers = strings.map::<Type2, _>(|s| s.parse().unwrap());
+/// pool.give(numbers);
+///
+/// // The allocation of the numbers is reused.
+/// let strings = pool.take::<Type1>();
+///
+/// assert_eq!(strings.capacity(), 16);
+/// assert_eq!(pool.stats().hits, 1);
+/// ```
+pub struct UnionVecPool<U: TypeUnion> {
+    buffers: Vec<Vec<U::Union>>,
+    max_retained_capacity: Option<usize>,
+    max_retained_buffers: Option<usize>,
+    stats: PoolStats,
+}
+
+impl<U: TypeUnion> UnionVecPool<U> {
+    /// Constructs a new, empty pool, that retains every allocation given back.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            buffers: Vec::new(),
+            max_retained_capacity: None,
+            max_retained_buffers: None,
+            stats: PoolStats::default(),
+        }
+    }
+
+    /// Limits the capacity of the union-vectors the pool retains. Union-vectors given back with a
+    /// bigger capacity are freed instead.
+    #[inline]
+    pub fn set_max_retained_capacity(&mut self, max: Option<usize>) {
+        self.max_retained_capacity = max;
+        self.buffers.retain(|buffer| max.is_none_or(|max| buffer.capacity() <= max));
+    }
+
+    /// Limits the number of allocations the pool retains at once. Union-vectors given back when
+    /// the pool is full are freed instead.
+    #[inline]
+    pub fn set_max_retained_buffers(&mut self, max: Option<usize>) {
+        self.max_retained_buffers = max;
+
+        if let Some(max) = max {
+            self.buffers.truncate(max);
+        }
+    }
+
+    /// Returns the number of allocations currently retained.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Returns `true` if the pool retains no allocations.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Returns the statistics of the pool.
+    #[inline]
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+
+    /// Takes an empty union-vector out of the pool, holding the type selected by `S`.
+    /// A retained allocation is reused when available.
+    #[inline]
+    pub fn take<S>(&mut self) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>,
+    {
+        match self.buffers.pop() {
+            Some(buffer) => {
+                self.stats.hits += 1;
+                unsafe { UnionVec::from_data_unchecked(buffer) }
+            }
+            None => {
+                self.stats.misses += 1;
+                UnionVec::new()
+            }
+        }
+    }
+
+    /// Takes an empty union-vector out of the pool, holding the type selected by `S`, with a
+    /// capacity of at least `capacity`. The smallest retained allocation that is big enough is
+    /// reused when available.
+    #[inline]
+    pub fn take_with_capacity<S>(&mut self, capacity: usize) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>,
+    {
+        let best = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter(|&(_, buffer)| buffer.capacity() >= capacity)
+            .min_by_key(|&(_, buffer)| buffer.capacity())
+            .map(|(idx, _)| idx);
+
+        match best {
+            Some(idx) => {
+                self.stats.hits += 1;
+                unsafe { UnionVec::from_data_unchecked(self.buffers.swap_remove(idx)) }
+            }
+            None => {
+                self.stats.misses += 1;
+                UnionVec::with_capacity(capacity)
+            }
+        }
+    }
+
+    /// Gives a union-vector back to the pool. The union-vector is cleared as its current type `T`,
+    /// and its allocation is retained if it is within the limits of the pool.
+    #[inline]
+    pub fn give<T>(&mut self, mut union_vec: UnionVec<T, U>) {
+        union_vec.clear();
+
+        let capacity = union_vec.capacity();
+        let fits_capacity = self.max_retained_capacity.is_none_or(|max| capacity <= max);
+        let fits_buffers = self
+            .max_retained_buffers
+            .is_none_or(|max| self.buffers.len() < max);
+
+        if capacity != 0 && mem::size_of::<U::Union>() != 0 && fits_capacity && fits_buffers {
+            self.stats.retained += 1;
+            self.buffers.push(union_vec.take_data());
+        } else {
+            self.stats.discarded += 1;
+        }
+    }
+}
+
+impl<U: TypeUnion> Default for UnionVecPool<U> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use index::{Type1, Type2};
+
+    #[test]
+    fn test_pool_reuses_allocation() {
+        let mut pool = UnionVecPool::<(String, u64)>::new();
+
+        let mut strings = pool.take_with_capacity::<Type1>(8);
+        strings.push(String::from("a"));
+        pool.give(strings);
+
+        let numbers = pool.take::<Type2>();
+
+        assert_eq!(numbers.len(), 0);
+        assert_eq!(numbers.capacity(), 8);
+        assert_eq!(
+            pool.stats(),
+            PoolStats {
+                hits: 1,
+                misses: 1,
+                retained: 1,
+                discarded: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_pool_limits() {
+        let mut pool = UnionVecPool::<(String, u64)>::new();
+        pool.set_max_retained_capacity(Some(4));
+        pool.set_max_retained_buffers(Some(1));
+
+        pool.give(pool_vec(16));
+        pool.give(pool_vec(4));
+        pool.give(pool_vec(2));
+
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.stats().retained, 1);
+        assert_eq!(pool.stats().discarded, 2);
+
+        // The only retained allocation is too small.
+        let numbers = pool.take_with_capacity::<Type2>(10);
+        assert_eq!(pool.stats().misses, 1);
+        assert_eq!(pool.len(), 1);
+        assert!(numbers.capacity() >= 10);
+    }
+
+    fn pool_vec(capacity: usize) -> UnionVec<u64, (String, u64)> {
+        UnionVec::with_capacity(capacity)
+    }
+}
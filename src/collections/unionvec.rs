@@ -1,21 +1,89 @@
+use std::alloc::{handle_alloc_error, realloc, Layout};
+use std::any;
+use std::cmp;
+use std::collections::VecDeque;
+use std::convert::{Infallible, TryInto};
+use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr;
+use std::slice;
 
-use select::{Select, SelectHandle, Selector, TypeSelect, TypeUnion};
+use collections::UnionCollection;
+use either::Either;
+use pod::Pod;
+use select::{
+    Contains, EnumUnion, Select, SelectHandle, Selected, Selector, SubsetOf, TypeSelect,
+    TypeUnion,
+};
+
+#[cfg(feature = "checked")]
+mod checked;
+mod ffi;
+mod io;
+mod par;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod snapshot;
+mod sort;
+
+pub use self::ffi::FfiSlice;
+pub use self::io::UnionVecReader;
+pub use self::snapshot::SnapshotError;
 
 /// A UnionVec can be used to hold multiple datatypes, but only one at a time.
 /// It's possible to change between types, but only for all items, and not individually per item.
 ///
 /// Changing between types can be done with [`UnionVec::change_to`], [`UnionVec::map`] and
 /// [`UnionVec::into_vec`]. It's also possible to discard values, with [`UnionVec::filter_map`]
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Default, Hash)]
-pub struct UnionVec<T: 'static, U: TypeUnion> {
+///
+/// With the `checked` feature, every union-vector also carries the `TypeId` of `T` at runtime,
+/// which is verified by every typed accessor. This catches a union-vector that is transmuted or
+/// cast to another current type.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash)]
+pub struct UnionVec<T, U: TypeUnion> {
     data: Vec<U::Union>,
     marker: PhantomData<T>,
+
+    #[cfg(feature = "checked")]
+    tag: any::TypeId,
+}
+
+/// The two union-vectors returned by [`UnionVec::partition_map`].
+pub type Partitioned<U, S1, S2> = (UnionVec<Selected<U, S1>, U>, UnionVec<Selected<U, S2>, U>);
+
+/// Describes how the allocation of a [`UnionVec`] is used, see [`UnionVec::memory_report`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MemoryReport {
+    /// The number of elements in the union-vector.
+    pub len: usize,
+
+    /// The number of elements the union-vector can hold without reallocating.
+    pub capacity: usize,
+
+    /// The size of the Union, which is the stride between the elements.
+    pub union_size: usize,
+
+    /// The alignment of the Union.
+    pub union_align: usize,
+
+    /// The size of the current type of the union-vector.
+    pub element_size: usize,
+
+    /// The number of bytes allocated.
+    pub bytes_capacity: usize,
+
+    /// The number of bytes taken by the elements, including the padding of the Union.
+    pub bytes_used: usize,
+
+    /// The number of bytes taken by the padding of the Union, for all elements.
+    pub bytes_wasted: usize,
+
+    /// The number of bytes allocated, but not taken by any element.
+    pub bytes_unused: usize,
 }
 
-impl<T: 'static, U: TypeUnion> UnionVec<T, U> {
+impl<T, U: TypeUnion> UnionVec<T, U> {
     /// Constructs a new, empty `UnionVec<T, U>`.
     /// `T` is the current type of the vector, `U` a tuple of types the vector can change to.
     /// The UnionVector will not allocate until elements are pushed onto it.
@@ -33,6 +101,8 @@ impl<T: 'static, U: TypeUnion> UnionVec<T, U> {
         Self {
             data: Vec::new(),
             marker: PhantomData,
+            #[cfg(feature = "checked")]
+            tag: checked::tag_of::<T>(),
         }
     }
 
@@ -58,37 +128,221 @@ impl<T: 'static, U: TypeUnion> UnionVec<T, U> {
         Self {
             data: Vec::with_capacity(n),
             marker: PhantomData,
+            #[cfg(feature = "checked")]
+            tag: checked::tag_of::<T>(),
         }
     }
 
+    /// Reserves capacity for at least `additional` more elements, see [`Vec::reserve`].
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional)
+    }
+
+    /// Reserves capacity for exactly `additional` more elements, see [`Vec::reserve_exact`].
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.data.reserve_exact(additional)
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.data.len()
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
     #[inline]
     pub fn capacity(&self) -> usize {
         self.data.capacity()
     }
 
+    /// Returns the number of bytes the union-vector has allocated.
+    #[inline]
+    pub fn bytes_capacity(&self) -> usize {
+        self.capacity() * U::SIZE
+    }
+
+    /// Returns the number of elements of the type selected by `S` that would fit into the
+    /// allocation of the union-vector, if they were stored without the padding of the Union.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type1;
+    ///
+    /// let union_vec = UnionVec::<u64, (u16, u64)>::with_capacity(10);
+    ///
+    /// assert_eq!(union_vec.capacity_as::<Type1>(), 40);
+    /// ```
+    #[inline]
+    pub fn capacity_as<S>(&self) -> usize
+    where
+        S: Selector,
+        U: Select<S>,
+    {
+        match mem::size_of::<<U as Select<S>>::Output>() {
+            0 => usize::MAX,
+            size => self.bytes_capacity() / size,
+        }
+    }
+
+    /// Returns a [`MemoryReport`], describing how the allocation of the union-vector is used.
+    #[inline]
+    pub fn memory_report(&self) -> MemoryReport {
+        let unused = self.capacity() - self.len();
+
+        MemoryReport {
+            len: self.len(),
+            capacity: self.capacity(),
+            union_size: U::SIZE,
+            union_align: U::ALIGN,
+            element_size: mem::size_of::<T>(),
+            bytes_capacity: self.bytes_capacity(),
+            bytes_used: self.len() * U::SIZE,
+            bytes_wasted: self.len() * U::waste_for::<T>(),
+            bytes_unused: unused * U::SIZE,
+        }
+    }
+
     #[inline]
     pub fn push(&mut self, item: T) {
+        self.check_tag();
+
         let item = SelectHandle::<T, U>::from(item);
         self.data.push(item.into_inner())
     }
 
     #[inline]
     pub fn pop(&mut self) -> Option<T> {
+        self.check_tag();
         self.data.pop().map(|union| unsafe { union.cast::<T>() })
     }
 
+    /// Clears the union-vector, dropping every element as the current type `T`.
+    /// The capacity of the union-vector is not changed.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.check_tag();
+
+        let len = self.data.len();
+
+        unsafe {
+            self.data.set_len(0);
+
+            let ptr = self.data.as_mut_ptr();
+
+            for i in 0..len {
+                ptr::drop_in_place(ptr.add(i) as *mut T);
+            }
+        }
+    }
+
+    /// Creates a union-vector from a Vector of Unions, that each hold a `T`.
+    ///
+    /// With the `checked` feature, the union-vector is tagged with `T`. What the Unions hold can
+    /// not be verified, but any later access as another type is.
+    ///
+    /// # Safety
+    ///
+    /// Every element of `data` must hold a value of `T`.
+    #[inline]
+    pub unsafe fn from_data(data: Vec<U::Union>) -> Self {
+        Self::from_data_unchecked(data)
+    }
+
+    /// Creates a union-vector from a Vector of Unions, that each hold a `T`.
+    #[inline]
+    pub(crate) unsafe fn from_data_unchecked(data: Vec<U::Union>) -> Self {
+        Self {
+            data,
+            marker: PhantomData,
+            #[cfg(feature = "checked")]
+            tag: checked::tag_of::<T>(),
+        }
+    }
+
+    /// Returns the underlying Vector of Unions, that each hold a `T`.
     #[inline]
     pub fn into_data(self) -> Vec<U::Union> {
+        self.take_data()
+    }
+
+    /// Returns the underlying Vector of Unions, that each hold a `T`.
+    #[inline]
+    pub(crate) fn take_data(self) -> Vec<U::Union> {
+        self.check_tag();
+
         let data = unsafe { ptr::read(&self.data) };
         mem::forget(self);
         data
     }
 
+    /// Decomposes the union-vector into a pointer to its Unions, its length and its capacity.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, String)>::new();
+    /// union_vec.push(10);
+    ///
+    /// let (ptr, len, capacity) = union_vec.into_raw_parts();
+    ///
+    /// let mut union_vec =
+    ///     unsafe { UnionVec::<u32, (u32, String)>::from_raw_parts(ptr, len, capacity) };
+    ///
+    /// assert_eq!(union_vec.pop(), Some(10));
+    /// ```
+    #[inline]
+    pub fn into_raw_parts(self) -> (*mut U::Union, usize, usize) {
+        let mut data = mem::ManuallyDrop::new(self.into_data());
+
+        (data.as_mut_ptr(), data.len(), data.capacity())
+    }
+
+    /// Creates a union-vector from a pointer to Unions, a length and a capacity, see
+    /// [`Vec::from_raw_parts`].
+    ///
+    /// # Safety
+    ///
+    /// The pointer, length and capacity must meet the requirements of [`Vec::from_raw_parts`],
+    /// and the first `len` Unions must each hold a value of `T`.
+    #[inline]
+    pub unsafe fn from_raw_parts(ptr: *mut U::Union, len: usize, capacity: usize) -> Self {
+        Self::from_data(Vec::from_raw_parts(ptr, len, capacity))
+    }
+
+    /// Returns a pointer to the first Union of the union-vector.
+    #[inline]
+    pub fn as_ptr(&self) -> *const U::Union {
+        self.data.as_ptr()
+    }
+
+    /// Returns a mutable pointer to the first Union of the union-vector.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut U::Union {
+        self.data.as_mut_ptr()
+    }
+
+    /// Verifies that the union-vector holds a `T`, when the `checked` feature is enabled.
+    #[inline]
+    fn check_tag(&self) {
+        #[cfg(feature = "checked")]
+        assert_eq!(
+            self.tag,
+            checked::tag_of::<T>(),
+            "The union-vector does not hold the type it is accessed as"
+        );
+    }
+
     /// Clears the underlying Vec, and returns a new [`UnionVec`].
     /// The returned UnionVec will have the same capacity as the old one had.
     #[inline]
@@ -97,14 +351,190 @@ impl<T: 'static, U: TypeUnion> UnionVec<T, U> {
         S: Selector,
         U: Select<S>,
     {
-        self.data.clear();
+        self.clear();
 
-        UnionVec {
-            data: self.into_data(),
-            marker: PhantomData,
+        unsafe { UnionVec::from_data_unchecked(self.take_data()) }
+    }
+
+    /// Converts `self` into a [`UnionVec`] that can change to more types.
+    /// Every type of `U` must also be a type of `U2`, which is checked at compiletime.
+    ///
+    /// When the Union of `U2` has the same size and alignment as the Union of `U`, the underlying
+    /// Vec is reused as-is. Otherwise the elements are moved to the new stride, which may
+    /// reallocate.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let mut union_vec = UnionVec::<u8, (u8, u16)>::new();
+    /// union_vec.push(10);
+    ///
+    /// let mut union_vec = union_vec.widen::<(u8, u16, u64), _>();
+    ///
+    /// assert_eq!(union_vec.pop(), Some(10));
+    /// ```
+    #[inline]
+    pub fn widen<U2, I>(self) -> UnionVec<T, U2>
+    where
+        U2: TypeUnion,
+        U: SubsetOf<U2, I>,
+    {
+        unsafe {
+            UnionVec::from_data_unchecked(restride::<T, U::Union, U2::Union>(self.take_data()))
+        }
+    }
+
+    /// Converts `self` into a [`UnionVec`] that can change to less types.
+    /// The current type `T` must be a type of `U2`, which is checked at compiletime.
+    ///
+    /// When the Union of `U2` is smaller than the Union of `U`, the elements are moved to the
+    /// smaller stride in place, and the allocation is shrunk where possible.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let mut union_vec = UnionVec::<u8, (u8, u16, u64)>::new();
+    /// union_vec.push(10);
+    ///
+    /// let mut union_vec = union_vec.narrow::<(u8, u16), _>();
+    ///
+    /// assert_eq!(union_vec.pop(), Some(10));
+    /// ```
+    ///
+    /// ```compile_fail
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let union_vec = UnionVec::<u64, (u8, u16, u64)>::new();
+    ///
+    /// // u64 is not a type of (u8, u16).
+    /// let union_vec = union_vec.narrow::<(u8, u16), _>();
+    /// ```
+    #[inline]
+    pub fn narrow<U2, S>(self) -> UnionVec<T, U2>
+    where
+        S: Selector,
+        U2: TypeUnion + Contains<T, S>,
+    {
+        unsafe {
+            UnionVec::from_data_unchecked(restride::<T, U::Union, U2::Union>(self.take_data()))
         }
     }
 
+    /// Changes the current type of the union-vector to the type selected by `S`, without touching
+    /// the elements. The bytes of every `T` are reinterpreted as the selected type.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, [u8; 4])>::new();
+    /// union_vec.push(u32::from_be(0x01020304));
+    ///
+    /// let mut union_vec = union_vec.reinterpret::<Type2>();
+    ///
+    /// assert_eq!(union_vec.pop(), Some([1, 2, 3, 4]));
+    /// ```
+    ///
+    /// `T` and the selected type must have the same size, which is checked at compiletime:
+    /// ```compile_fail
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let union_vec = UnionVec::<u32, (u32, u64)>::new();
+    ///
+    /// // u32 and u64 differ in size.
+    /// let union_vec = union_vec.reinterpret::<Type2>();
+    /// ```
+    #[inline]
+    pub fn reinterpret<S>(self) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>,
+        T: Pod,
+        <U as Select<S>>::Output: Pod,
+    {
+        const {
+            assert!(
+                mem::size_of::<T>() == mem::size_of::<<U as Select<S>>::Output>(),
+                "Can only reinterpret between types of the same size"
+            )
+        };
+
+        unsafe { UnionVec::from_data_unchecked(self.take_data()) }
+    }
+
+    /// Returns a view of the elements as a slice of `P`.
+    ///
+    /// # Panic
+    ///
+    /// When `T` does not fill the entire Union, the elements are not laid out contiguous, and
+    /// this function will panic. It also panics when `P` is zero-sized, when `P` has a bigger
+    /// alignment than the Union, or when the bytes of the elements can not be divided into `P`'s.
+    #[inline]
+    pub fn cast_slice<P: Pod>(&self) -> &[P]
+    where
+        T: Pod,
+    {
+        let len = self.cast_slice_len::<P>();
+
+        unsafe { slice::from_raw_parts(self.data.as_ptr() as *const P, len) }
+    }
+
+    /// Returns a mutable view of the elements as a slice of `P`.
+    ///
+    /// # Panic
+    ///
+    /// See [`UnionVec::cast_slice`].
+    #[inline]
+    pub fn cast_slice_mut<P: Pod>(&mut self) -> &mut [P]
+    where
+        T: Pod,
+    {
+        let len = self.cast_slice_len::<P>();
+
+        unsafe { slice::from_raw_parts_mut(self.data.as_mut_ptr() as *mut P, len) }
+    }
+
+    /// Returns the number of `P`'s the elements can be viewed as.
+    #[inline]
+    fn cast_slice_len<P: Pod>(&self) -> usize {
+        self.check_tag();
+
+        assert_eq!(
+            mem::size_of::<T>(),
+            U::SIZE,
+            "Can only cast the elements to a slice when they fill the entire Union"
+        );
+        assert!(mem::size_of::<P>() != 0, "Can not cast to a slice of zero-sized types");
+        assert!(
+            mem::align_of::<P>() <= U::ALIGN,
+            "Can not cast to a slice of a type with a bigger alignment than the Union"
+        );
+
+        let bytes = self.len() * U::SIZE;
+
+        assert_eq!(
+            bytes % mem::size_of::<P>(),
+            0,
+            "Can not cast to a slice of a type that does not divide the elements"
+        );
+
+        bytes / mem::size_of::<P>()
+    }
+
     /// For each element in the collection, the closure is called.
     /// The closure may return any type the Union can turn into. Any closure that does not return
     /// a type the Union can turn into, will result in a compiletime error.
@@ -151,7 +581,7 @@ impl<T: 'static, U: TypeUnion> UnionVec<T, U> {
          */
 
         // 1
-        let mut data = self.into_data();
+        let mut data = self.take_data();
 
         // 2
         let len = data.len();
@@ -189,10 +619,7 @@ impl<T: 'static, U: TypeUnion> UnionVec<T, U> {
             data.set_len(len);
         }
 
-        UnionVec {
-            data,
-            marker: PhantomData,
-        }
+        unsafe { UnionVec::from_data_unchecked(data) }
     }
 
     /// For each element in the collection, the closure is called. The closure returns an Option,
@@ -227,67 +654,774 @@ impl<T: 'static, U: TypeUnion> UnionVec<T, U> {
     /// ```
     /// # Panic
     ///
-    /// When the closure panics, the internal Vector is leaked.
+    /// When the closure panics, the elements are dropped.
     #[inline]
     pub fn filter_map<S: Selector, F>(self, f: F) -> UnionVec<<U as Select<S>>::Output, U>
     where
         U: Select<S>,
         F: Fn(T) -> Option<<U as Select<S>>::Output>,
     {
-        let mut data = self.into_data();
-        let len = data.len();
-        let mut nones: usize = 0;
-
-        unsafe {
-            data.set_len(0);
-
-            let ptr = data.as_mut_ptr();
-
-            for i in 0..len as isize {
-                let read_ptr: *mut U::Union = ptr.offset(i);
-                let write_ptr: *mut U::Union = ptr.offset(i - nones as isize);
-
-                let union_t: SelectHandle<T, U> = SelectHandle::from_inner(ptr::read(read_ptr));
-
-                let t = union_t.into();
-
-                let u = match f(t) {
-                    Some(item) => item,
-                    None => {
-                        nones += 1;
-
-                        continue;
-                    }
-                };
-
-                let union_u: SelectHandle<<U as Select<S>>::Output, U> =
-                    SelectHandle::from_unchecked(u);
-                ptr::write(write_ptr, union_u.into_inner());
-            }
-
-            data.set_len(len - nones);
-        }
-
-        UnionVec {
-            data,
-            marker: PhantomData,
-        }
+        unsafe { self.filter_map_unchecked(f) }
     }
 
+    /// Converts every element into the type selected by `S`, with its [`Into`] implementation.
+    /// The conversion is done in place, like [`UnionVec::map`] does.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, u64)>::new();
+    /// union_vec.push(10);
+    ///
+    /// let mut union_vec = union_vec.convert::<Type2>();
+    ///
+    /// assert_eq!(union_vec.pop(), Some(10u64));
+    /// ```
+    /// # Panic
+    ///
+    /// When a conversion panics, the elements are dropped.
     #[inline]
-    pub fn into_vec(self) -> Vec<T> {
-        unimplemented!()
+    pub fn convert<S: Selector>(self) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        U: Select<S>,
+        T: Into<<U as Select<S>>::Output>,
+    {
+        unsafe { self.filter_map_unchecked(|t| Some(t.into())) }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use index::{Type1, Type2};
-    #[test]
-    fn test_unionvec_change_to() {
-        let mut union_vec = UnionVec::<String, (String, u64)>::new();
-        union_vec.push(String::from("test"));
+    /// Converts every element into the type selected by `S`, with its [`TryInto`] implementation.
+    /// The conversion is done in place, like [`UnionVec::map`] does.
+    ///
+    /// When an element fails to convert, all elements are dropped, and the error is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let mut union_vec = UnionVec::<i64, (i64, u8)>::new();
+    /// union_vec.push(10);
+    /// union_vec.push(20);
+    ///
+    /// let mut union_vec = union_vec.try_convert::<Type2>().unwrap();
+    /// assert_eq!(union_vec.pop(), Some(20u8));
+    ///
+    /// let mut union_vec = UnionVec::<i64, (i64, u8)>::new();
+    /// union_vec.push(-1);
+    ///
+    /// assert!(union_vec.try_convert::<Type2>().is_err());
+    /// ```
+    /// # Panic
+    ///
+    /// When a conversion panics, the elements are dropped.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn try_convert<S: Selector>(
+        self,
+    ) -> Result<
+        UnionVec<<U as Select<S>>::Output, U>,
+        <T as TryInto<<U as Select<S>>::Output>>::Error,
+    >
+    where
+        U: Select<S>,
+        T: TryInto<<U as Select<S>>::Output>,
+    {
+        unsafe { self.try_filter_map_unchecked(|t| t.try_into().map(Some)) }
+    }
+
+    /// Converts every element into the type selected by `S`, with its [`TryInto`] implementation.
+    /// Elements that fail to convert are discarded, like [`UnionVec::filter_map`] does.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let mut union_vec = UnionVec::<i64, (i64, u8)>::new();
+    ///
+    /// for n in [1, -2, 300, 4] {
+    ///     union_vec.push(n);
+    /// }
+    ///
+    /// let union_vec = union_vec.convert_lossy::<Type2>();
+    ///
+    /// assert_eq!(union_vec.into_vec(), vec![1, 4]);
+    /// ```
+    /// # Panic
+    ///
+    /// When a conversion panics, the elements are dropped.
+    #[inline]
+    pub fn convert_lossy<S: Selector>(self) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        U: Select<S>,
+        T: TryInto<<U as Select<S>>::Output>,
+    {
+        unsafe { self.filter_map_unchecked(|t| t.try_into().ok()) }
+    }
+
+    /// For each element in the collection, the closure is called. The closure returns any number
+    /// of outputs, which are all written to the collection, in order.
+    ///
+    /// Outputs are written in place, as long as they do not overtake the elements that are not
+    /// yet read. Outputs that would overtake them are kept in a scratch buffer, and are moved into
+    /// place once enough elements are read. Whatever remains in the scratch buffer at the end is
+    /// appended, growing the allocation only as far as needed.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let mut union_vec = UnionVec::<String, (String, &str)>::new();
+    /// union_vec.push(String::from("the quick"));
+    /// union_vec.push(String::from("brown fox"));
+    ///
+    /// let tokens = union_vec.flat_map::<Type2, _, _>(|line| {
+    ///     line.split_whitespace()
+    ///         .map(|token| if token.len() > 3 { "long" } else { "short" })
+    ///         .collect::<Vec<_>>()
+    /// });
+    ///
+    /// assert_eq!(tokens.into_vec(), vec!["short", "long", "long", "short"]);
+    /// ```
+    /// # Panic
+    ///
+    /// When the closure panics, the internal Vector is leaked.
+    #[inline]
+    pub fn flat_map<S, I, F>(self, mut f: F) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>,
+        I: IntoIterator<Item = <U as Select<S>>::Output>,
+        F: FnMut(T) -> I,
+    {
+        let mut data = self.take_data();
+        let len = data.len();
+        let mut written = 0;
+        let mut spill = VecDeque::new();
+
+        unsafe {
+            data.set_len(0);
+
+            let ptr = data.as_mut_ptr();
+
+            let write = |index: usize, item: <U as Select<S>>::Output| {
+                let union_u: SelectHandle<<U as Select<S>>::Output, U> =
+                    SelectHandle::from_unchecked(item);
+                ptr::write(ptr.add(index), union_u.into_inner());
+            };
+
+            for i in 0..len {
+                let union_t: SelectHandle<T, U> = SelectHandle::from_inner(ptr::read(ptr.add(i)));
+                let t = union_t.into();
+
+                // Every slot from `written` up to and including `i` is free now. Spilled outputs
+                // come first, to keep the outputs in order.
+                while written <= i {
+                    match spill.pop_front() {
+                        Some(item) => {
+                            write(written, item);
+                            written += 1;
+                        }
+                        None => break,
+                    }
+                }
+
+                for item in f(t) {
+                    if spill.is_empty() && written <= i {
+                        write(written, item);
+                        written += 1;
+                    } else {
+                        spill.push_back(item);
+                    }
+                }
+            }
+
+            data.set_len(written);
+        }
+
+        let mut union_vec = unsafe { UnionVec::from_data_unchecked(data) };
+
+        union_vec.data.reserve_exact(spill.len());
+
+        for item in spill {
+            union_vec.push(item);
+        }
+
+        union_vec
+    }
+
+    /// For each element in the collection, the closure is called. The closure routes every element
+    /// to one of two outputs, by returning an [`Either`]. All `Left` outputs are written back to
+    /// the collection, in place, and all `Right` outputs are pushed onto a new [`UnionVec`].
+    ///
+    /// The first returned union-vector reuses the allocation of `self`, the second one is
+    /// allocated fresh.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::either::Either;
+    /// use unioncollections::index::{Type2, Type3};
+    ///
+    /// let mut union_vec = UnionVec::<&str, (&str, u64, String)>::new();
+    /// for s in vec!["10", "20", "3o", "40"] {
+    ///     union_vec.push(s);
+    /// }
+    ///
+    /// let (mut parsed, mut errors) = union_vec.partition_map::<Type2, Type3, _>(|s| {
+    ///     match s.parse() {
+    ///         Ok(n) => Either::Left(n),
+    ///         Err(e) => Either::Right(format!("{}: {}", s, e)),
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(parsed.len(), 3);
+    /// assert_eq!(parsed.capacity(), 4);
+    /// assert_eq!(parsed.pop(), Some(40));
+    ///
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors.pop(), Some(String::from("3o: invalid digit found in string")));
+    /// ```
+    /// # Panic
+    ///
+    /// When the closure panics, the internal Vector is leaked.
+    #[inline]
+    pub fn partition_map<S1, S2, F>(self, mut f: F) -> Partitioned<U, S1, S2>
+    where
+        S1: Selector,
+        S2: Selector,
+        U: Select<S1> + Select<S2>,
+        F: FnMut(T) -> Either<Selected<U, S1>, Selected<U, S2>>,
+    {
+        let mut data = self.take_data();
+        let len = data.len();
+        let mut rights = UnionVec::new();
+
+        unsafe {
+            data.set_len(0);
+
+            let ptr = data.as_mut_ptr();
+
+            for i in 0..len {
+                let read_ptr: *mut U::Union = ptr.add(i);
+                let write_ptr: *mut U::Union = ptr.add(i - rights.len());
+
+                let union_t: SelectHandle<T, U> = SelectHandle::from_inner(ptr::read(read_ptr));
+
+                match f(union_t.into()) {
+                    Either::Left(left) => {
+                        let union_left: SelectHandle<<U as Select<S1>>::Output, U> =
+                            SelectHandle::from_unchecked(left);
+                        ptr::write(write_ptr, union_left.into_inner());
+                    }
+                    Either::Right(right) => rights.push(right),
+                }
+            }
+
+            data.set_len(len - rights.len());
+        }
+
+        let lefts = unsafe { UnionVec::from_data_unchecked(data) };
+
+        (lefts, rights)
+    }
+
+    /// Combines the elements of `self` and `other` pairwise with the closure. The outputs are
+    /// written into the allocation of `self`, in place.
+    ///
+    /// When one of the union-vectors is longer than the other, its leftover elements are returned
+    /// as well: `Either::Left` holds the leftovers of `self`, and `Either::Right` the leftovers of
+    /// `other`. The leftovers of `other` keep its allocation, the leftovers of `self` are moved
+    /// into a new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::either::Either;
+    /// use unioncollections::index::Type3;
+    ///
+    /// let mut keys = UnionVec::<&str, (&str, u32, (&str, u32))>::new();
+    /// keys.push("a");
+    /// keys.push("b");
+    ///
+    /// let mut values = UnionVec::<u32, (u32, u64)>::new();
+    /// values.push(1);
+    /// values.push(2);
+    /// values.push(3);
+    ///
+    /// let (pairs, leftover) = keys.zip_map::<Type3, _, _, _>(values, |k, v| (k, v));
+    ///
+    /// assert_eq!(pairs.into_vec(), vec![("a", 1), ("b", 2)]);
+    ///
+    /// match leftover {
+    ///     Some(Either::Right(values)) => assert_eq!(values.into_vec(), vec![3]),
+    ///     _ => panic!("values was longer"),
+    /// }
+    /// ```
+    /// # Panic
+    ///
+    /// When the closure panics, both internal Vectors are leaked.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn zip_map<S, T2, U2: TypeUnion, F>(
+        self,
+        other: UnionVec<T2, U2>,
+        mut f: F,
+    ) -> (
+        UnionVec<<U as Select<S>>::Output, U>,
+        Option<Either<UnionVec<T, U>, UnionVec<T2, U2>>>,
+    )
+    where
+        S: Selector,
+        U: Select<S>,
+        F: FnMut(T, T2) -> <U as Select<S>>::Output,
+    {
+        let mut data = self.take_data();
+        let mut other_data = other.take_data();
+
+        let len = data.len();
+        let other_len = other_data.len();
+        let zipped = cmp::min(len, other_len);
+
+        unsafe {
+            data.set_len(0);
+            other_data.set_len(0);
+
+            let ptr = data.as_mut_ptr();
+            let other_ptr = other_data.as_mut_ptr();
+
+            for i in 0..zipped {
+                let union_t: SelectHandle<T, U> = SelectHandle::from_inner(ptr::read(ptr.add(i)));
+                let union_t2: SelectHandle<T2, U2> =
+                    SelectHandle::from_inner(ptr::read(other_ptr.add(i)));
+
+                let u = f(union_t.into(), union_t2.into());
+
+                let union_u: SelectHandle<<U as Select<S>>::Output, U> =
+                    SelectHandle::from_unchecked(u);
+                ptr::write(ptr.add(i), union_u.into_inner());
+            }
+
+            let leftover = if len > zipped {
+                let mut rest = Vec::with_capacity(len - zipped);
+                ptr::copy_nonoverlapping(ptr.add(zipped), rest.as_mut_ptr(), len - zipped);
+                rest.set_len(len - zipped);
+
+                Some(Either::Left(UnionVec::from_data_unchecked(rest)))
+            } else if other_len > zipped {
+                ptr::copy(other_ptr.add(zipped), other_ptr, other_len - zipped);
+                other_data.set_len(other_len - zipped);
+
+                Some(Either::Right(UnionVec::from_data_unchecked(other_data)))
+            } else {
+                None
+            };
+
+            data.set_len(zipped);
+
+            (UnionVec::from_data_unchecked(data), leftover)
+        }
+    }
+
+    /// The implementation of [`UnionVec::filter_map`], for any output type `O`.
+    /// The caller has to make sure `O` is one of the types of `U`.
+    #[inline]
+    pub(crate) unsafe fn filter_map_unchecked<O, F>(self, mut f: F) -> UnionVec<O, U>
+    where
+        F: FnMut(T) -> Option<O>,
+    {
+        match self.try_filter_map_unchecked(|t| Ok::<_, Infallible>(f(t))) {
+            Ok(union_vec) => union_vec,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Calls the closure for every element, and writes every `Some` it returns back in place.
+    /// When the closure returns an error, every element is dropped and the error is returned.
+    /// The caller has to make sure `O` is one of the types of `U`.
+    ///
+    /// This is the in-place loop of every conversion that keeps the allocation. When the closure
+    /// panics or fails, the outputs written so far are dropped as `O`, and the elements not yet
+    /// read as `T`.
+    #[inline]
+    pub(crate) unsafe fn try_filter_map_unchecked<O, E, F>(
+        self,
+        mut f: F,
+    ) -> Result<UnionVec<O, U>, E>
+    where
+        F: FnMut(T) -> Result<Option<O>, E>,
+    {
+        let mut data = self.take_data();
+        let len = data.len();
+
+        data.set_len(0);
+
+        let mut guard = InPlaceGuard::<T, O, U> {
+            data,
+            len,
+            read: 0,
+            written: 0,
+            marker: PhantomData,
+        };
+
+        let ptr = guard.data.as_mut_ptr();
+
+        while guard.read < len {
+            let union_t: SelectHandle<T, U> =
+                SelectHandle::from_inner(ptr::read(ptr.add(guard.read)));
+            guard.read += 1;
+
+            if let Some(u) = f(union_t.into())? {
+                let union_u: SelectHandle<O, U> = SelectHandle::from_unchecked(u);
+                ptr::write(ptr.add(guard.written), union_u.into_inner());
+                guard.written += 1;
+            }
+        }
+
+        let mut data = mem::take(&mut guard.data);
+        data.set_len(guard.written);
+        mem::forget(guard);
+
+        Ok(UnionVec::from_data_unchecked(data))
+    }
+
+    /// Converts the union-vector into a Vec of its current type.
+    ///
+    /// The elements are moved out of the stride of the Union. When `T` has the same alignment as
+    /// the Union, this is done in place, and the allocation is shrunk to fit. Otherwise the
+    /// elements are moved into a new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let mut union_vec = UnionVec::<&str, (&str, u64)>::new();
+    ///
+    /// for s in vec!["10", "20", "30"] {
+    ///     union_vec.push(s);
+    /// }
+    ///
+    /// let v = union_vec.map::<Type2, _>(|s| s.parse().unwrap()).into_vec();
+    ///
+    /// assert_eq!(v, vec![10, 20, 30]);
+    /// ```
+    #[inline]
+    pub fn into_vec(self) -> Vec<T> {
+        unsafe { restride::<T, U::Union, T>(self.take_data()) }
+    }
+
+    /// Converts the union-vector into a Vec of enums, with a variant for every type of `U`.
+    /// Every element becomes the variant of `T`, which is selected by `S` at compiletime. When `T`
+    /// occurs more than once in `U`, `S` can not be infered and has to be given.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::union::OneOf2;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (String, u32)>::new();
+    /// union_vec.push(1);
+    ///
+    /// assert_eq!(union_vec.into_enum_vec(), vec![OneOf2::Type2(1)]);
+    /// ```
+    #[inline]
+    pub fn into_enum_vec<S>(self) -> Vec<U::Enum>
+    where
+        S: Selector,
+        U: EnumUnion + Contains<T, S>,
+    {
+        self.take_data()
+            .into_iter()
+            .map(|union| unsafe { U::union_into_enum(S::INDEX, union) })
+            .collect()
+    }
+}
+
+impl<T, U: TypeUnion> UnionCollection<T, U> for UnionVec<T, U> {
+    type Rebind<S>
+        = UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>;
+
+    #[inline]
+    fn len(&self) -> usize {
+        UnionVec::len(self)
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        UnionVec::capacity(self)
+    }
+
+    #[inline]
+    fn change_to<S>(self) -> Self::Rebind<S>
+    where
+        S: Selector,
+        U: Select<S>,
+    {
+        UnionVec::change_to::<S>(self)
+    }
+
+    #[inline]
+    fn map<S, F>(self, f: F) -> Self::Rebind<S>
+    where
+        S: Selector,
+        U: Select<S>,
+        F: Fn(T) -> <U as Select<S>>::Output,
+    {
+        UnionVec::map::<S, F>(self, f)
+    }
+
+    #[inline]
+    fn filter_map<S, F>(self, f: F) -> Self::Rebind<S>
+    where
+        S: Selector,
+        U: Select<S>,
+        F: Fn(T) -> Option<<U as Select<S>>::Output>,
+    {
+        UnionVec::filter_map::<S, F>(self, f)
+    }
+}
+
+/// The elements of a union-vector, formatted as a list of `T`'s.
+struct Elements<'a, T, U: TypeUnion>(&'a UnionVec<T, U>);
+
+impl<'a, T, U: TypeUnion> Elements<'a, T, U> {
+    #[inline]
+    fn iter(&self) -> impl Iterator<Item = &'a T> {
+        self.0.check_tag();
+        self.0
+            .data
+            .iter()
+            .map(|union| unsafe { &*(union as *const U::Union as *const T) })
+    }
+}
+
+impl<'a, T: fmt::Debug, U: TypeUnion> fmt::Debug for Elements<'a, T, U> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Formats the union-vector as `UnionVec<T>[..]`. The alternate form also shows the capacity,
+/// and the layout and members of the Union.
+///
+/// # Examples
+/// ```
+/// extern crate unioncollections;
+///
+/// use unioncollections::collections::unionvec::UnionVec;
+///
+/// let mut union_vec = UnionVec::<u64, (u64, u8)>::new();
+/// union_vec.push(1);
+/// union_vec.push(2);
+///
+/// assert_eq!(format!("{:?}", union_vec), "UnionVec<u64>[1, 2]");
+/// ```
+impl<T: fmt::Debug, U: TypeUnion> fmt::Debug for UnionVec<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = format!("UnionVec<{}>", any::type_name::<T>());
+
+        if f.alternate() {
+            f.debug_struct(&name)
+                .field("capacity", &self.capacity())
+                .field("union_size", &U::SIZE)
+                .field("union_align", &U::ALIGN)
+                .field("members", &U::member_type_names())
+                .field("elements", &Elements(self))
+                .finish()
+        } else {
+            f.write_str(&name)?;
+            Elements(self).fmt(f)
+        }
+    }
+}
+
+/// Formats the elements of the union-vector as a list, like `[1, 2, 3]`.
+impl<T: fmt::Display, U: TypeUnion> fmt::Display for UnionVec<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[")?;
+
+        for (i, t) in Elements(self).iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            t.fmt(f)?;
+        }
+
+        f.write_str("]")
+    }
+}
+
+impl<T, U: TypeUnion> Default for UnionVec<T, U> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U: TypeUnion> Drop for UnionVec<T, U> {
+    #[inline]
+    fn drop(&mut self) {
+        // The elements can not be dropped as `T` when they do not hold one, so they are leaked.
+        #[cfg(feature = "checked")]
+        {
+            if self.tag != checked::tag_of::<T>() {
+                mem::forget(mem::take(&mut self.data));
+                return;
+            }
+        }
+
+        self.clear();
+    }
+}
+
+/// The state of an in-place conversion from `T` to `O`, see
+/// [`UnionVec::try_filter_map_unchecked`]. The first `written` Unions hold an `O`, the Unions from
+/// `read` to `len` still hold a `T`. When the conversion is cut short, both are dropped.
+struct InPlaceGuard<T, O, U: TypeUnion> {
+    data: Vec<U::Union>,
+    len: usize,
+    read: usize,
+    written: usize,
+    marker: PhantomData<(T, O)>,
+}
+
+impl<T, O, U: TypeUnion> Drop for InPlaceGuard<T, O, U> {
+    fn drop(&mut self) {
+        let ptr = self.data.as_mut_ptr();
+
+        unsafe {
+            for i in 0..self.written {
+                ptr::drop_in_place(ptr.add(i) as *mut O);
+            }
+
+            for i in self.read..self.len {
+                ptr::drop_in_place(ptr.add(i) as *mut T);
+            }
+        }
+    }
+}
+
+/// Moves every `T` in `data` from the stride of `Old` to the stride of `New`.
+/// Each element of `data` must hold a `T` at its start, and `T` must fit into a `New`.
+///
+/// The allocation of `data` is reused when `Old` and `New` have the same alignment, and is
+/// reallocated in place to fit the new stride. Only when the alignments differ, a new allocation
+/// is made.
+unsafe fn restride<T, Old, New>(mut data: Vec<Old>) -> Vec<New> {
+    let len = data.len();
+    let cap = data.capacity();
+
+    let old_size = mem::size_of::<Old>();
+    let new_size = mem::size_of::<New>();
+    let same_align = mem::align_of::<Old>() == mem::align_of::<New>();
+
+    if old_size == new_size && same_align {
+        let ptr = data.as_mut_ptr() as *mut New;
+        mem::forget(data);
+
+        return Vec::from_raw_parts(ptr, len, cap);
+    }
+
+    if old_size != 0 && new_size != 0 && cap != 0 && same_align {
+        let old_ptr = data.as_mut_ptr() as *mut u8;
+        let old_layout = Layout::array::<Old>(cap).unwrap();
+        mem::forget(data);
+
+        // Moving to a smaller stride is done front to back, so no element is overwritten before
+        // it is moved, and moving to a bigger stride is done back to front for the same reason.
+        let (ptr, new_cap) = if new_size < old_size {
+            for i in 0..len {
+                let src = old_ptr.add(i * old_size) as *const T;
+                let dst = old_ptr.add(i * new_size) as *mut T;
+                ptr::copy(src, dst, 1);
+            }
+
+            let new_cap = old_layout.size() / new_size;
+            let new_bytes = new_cap * new_size;
+
+            if new_bytes == old_layout.size() {
+                (old_ptr, new_cap)
+            } else {
+                (realloc_or_abort(old_ptr, old_layout, new_bytes), new_cap)
+            }
+        } else {
+            let new_bytes = cap.checked_mul(new_size).expect("capacity overflow");
+            let ptr = realloc_or_abort(old_ptr, old_layout, new_bytes);
+
+            for i in (0..len).rev() {
+                let src = ptr.add(i * old_size) as *const T;
+                let dst = ptr.add(i * new_size) as *mut T;
+                ptr::copy(src, dst, 1);
+            }
+
+            (ptr, cap)
+        };
+
+        return Vec::from_raw_parts(ptr as *mut New, len, new_cap);
+    }
+
+    // A zero-sized `Old` reports a capacity of `usize::MAX`, so only `len` elements are needed.
+    let new_cap = if old_size == 0 { len } else { cap };
+    let mut new = Vec::<New>::with_capacity(new_cap);
+    let src = data.as_ptr() as *const u8;
+    let dst = new.as_mut_ptr() as *mut u8;
+
+    for i in 0..len {
+        let item_src = src.add(i * old_size) as *const T;
+        let item_dst = dst.add(i * new_size) as *mut T;
+        ptr::copy_nonoverlapping(item_src, item_dst, 1);
+    }
+
+    data.set_len(0);
+    new.set_len(len);
+    new
+}
+
+/// Reallocates `ptr` to `new_size` bytes, keeping the alignment of `layout`.
+unsafe fn realloc_or_abort(ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    let new_ptr = realloc(ptr, layout, new_size);
+
+    if new_ptr.is_null() {
+        handle_alloc_error(Layout::from_size_align_unchecked(new_size, layout.align()));
+    }
+
+    new_ptr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use index::{Type1, Type2, Type3};
+    use std::alloc::Layout;
+    use std::mem;
+    #[test]
+    fn test_unionvec_change_to() {
+        let mut union_vec = UnionVec::<String, (String, u64)>::new();
+        union_vec.push(String::from("test"));
         assert_eq!(union_vec.pop(), Some(String::from("test")));
 
         let mut union_vec = union_vec.change_to::<Type2>();
@@ -301,7 +1435,7 @@ mod tests {
     fn test_union_vec_map() {
         let mut union_vec = UnionVec::<&str, (&str, u64)>::new();
 
-        for s in vec!["10", "20", "30", "40"] {
+        for s in ["10", "20", "30", "40"] {
             union_vec.push(s);
         }
 
@@ -315,11 +1449,11 @@ mod tests {
     fn test_union_filter_map() {
         let mut union_vec = UnionVec::<&str, (&str, u64)>::new();
 
-        for s in vec!["10", "20", "30", "40e"] {
+        for s in ["10", "20", "30", "40e"] {
             union_vec.push(s);
         }
 
-        let mut union_vec = union_vec.filter_map::<Type2, _>(|s| s.parse().ok());
+        let union_vec = union_vec.filter_map::<Type2, _>(|s| s.parse().ok());
 
         // the last parse failed, so there are only 3 items in the vec.
         assert_eq!(union_vec.len(), 3);
@@ -327,4 +1461,369 @@ mod tests {
         // the capacity is still 4!
         assert_eq!(union_vec.capacity(), 4);
     }
+
+    #[test]
+    fn test_union_vec_widen() {
+        let mut union_vec = UnionVec::<u16, (u8, u16)>::with_capacity(4);
+
+        for n in 0..4 {
+            union_vec.push(n * 100);
+        }
+
+        let mut union_vec = union_vec.widen::<(u8, u16, u64), _>();
+
+        assert_eq!(mem::size_of::<<(u8, u16, u64) as TypeUnion>::Union>(), 8);
+        assert_eq!(union_vec.len(), 4);
+        assert_eq!(union_vec.capacity(), 4);
+
+        for n in (0..4).rev() {
+            assert_eq!(union_vec.pop(), Some(n * 100));
+        }
+    }
+
+    #[test]
+    fn test_union_vec_widen_zero_sized() {
+        let mut union_vec = UnionVec::<(), ((), ())>::new();
+
+        union_vec.push(());
+        union_vec.push(());
+
+        let mut union_vec = union_vec.widen::<((), u64), _>();
+
+        assert_eq!(union_vec.len(), 2);
+        assert!(union_vec.capacity() >= 2);
+
+        assert_eq!(union_vec.pop(), Some(()));
+        assert_eq!(union_vec.pop(), Some(()));
+        assert_eq!(union_vec.pop(), None);
+    }
+
+    #[test]
+    fn test_union_vec_narrow() {
+        let mut union_vec = UnionVec::<String, (String, u64, [u64; 8])>::with_capacity(3);
+
+        for s in ["a", "b", "c"] {
+            union_vec.push(String::from(s));
+        }
+
+        let mut union_vec = union_vec.narrow::<(u8, String), _>();
+
+        assert_eq!(union_vec.len(), 3);
+        assert!(union_vec.capacity() >= 3);
+        assert_eq!(union_vec.pop(), Some(String::from("c")));
+        assert_eq!(union_vec.pop(), Some(String::from("b")));
+        assert_eq!(union_vec.pop(), Some(String::from("a")));
+    }
+
+    #[test]
+    fn test_union_vec_memory_report() {
+        let mut union_vec = UnionVec::<u16, (u16, u64)>::with_capacity(4);
+        union_vec.push(1);
+        union_vec.push(2);
+
+        let report = union_vec.memory_report();
+
+        assert_eq!(report.union_size, 8);
+        assert_eq!(report.union_align, 8);
+        assert_eq!(report.element_size, 2);
+        assert_eq!(report.bytes_capacity, 32);
+        assert_eq!(report.bytes_used, 16);
+        assert_eq!(report.bytes_wasted, 12);
+        assert_eq!(report.bytes_unused, 16);
+    }
+
+    #[test]
+    fn test_type_union_layouts() {
+        type U = (u8, u32, [u16; 3]);
+
+        assert_eq!(<U as TypeUnion>::SIZE, 8);
+        assert_eq!(<U as TypeUnion>::ALIGN, 4);
+        assert_eq!(U::member_layouts()[2], Layout::new::<[u16; 3]>());
+        assert_eq!(U::member_type_ids()[1], ::std::any::TypeId::of::<u32>());
+        assert_eq!(U::member_type_names(), vec!["u8", "u32", "[u16; 3]"]);
+        assert_eq!(U::waste_for::<u8>(), 7);
+    }
+
+    #[test]
+    fn test_union_vec_reinterpret() {
+        let mut union_vec = UnionVec::<f32, (f32, u32, [u8; 4])>::new();
+        union_vec.push(1.0);
+        union_vec.push(-2.0);
+
+        assert_eq!(union_vec.cast_slice::<u32>(), &[0x3f80_0000, 0xc000_0000]);
+        assert_eq!(union_vec.cast_slice::<u16>().len(), 4);
+
+        let mut union_vec = union_vec.reinterpret::<Type2>();
+
+        assert_eq!(union_vec.pop(), Some(0xc000_0000));
+        assert_eq!(union_vec.pop(), Some(0x3f80_0000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_union_vec_cast_slice_padded() {
+        let mut union_vec = UnionVec::<u16, (u16, u64)>::new();
+        union_vec.push(1);
+
+        union_vec.cast_slice::<u16>();
+    }
+
+    #[test]
+    fn test_union_vec_into_vec() {
+        let mut union_vec = UnionVec::<u16, (u16, u64)>::with_capacity(4);
+
+        for n in 0..4 {
+            union_vec.push(n);
+        }
+
+        let v = union_vec.into_vec();
+        assert_eq!(v, vec![0, 1, 2, 3]);
+
+        // Both have an alignment of 8, so the allocation is shrunk in place.
+        let mut union_vec = UnionVec::<String, (String, [u64; 6])>::with_capacity(2);
+        union_vec.push(String::from("a"));
+        union_vec.push(String::from("b"));
+
+        let v = union_vec.into_vec();
+        assert_eq!(v, vec![String::from("a"), String::from("b")]);
+        assert_eq!(v.capacity(), 4);
+    }
+
+    #[test]
+    fn test_union_vec_partition_map() {
+        let mut union_vec = UnionVec::<u32, (u32, u64, String)>::with_capacity(6);
+
+        for n in 0..6 {
+            union_vec.push(n);
+        }
+
+        let (evens, odds) = union_vec.partition_map::<Type2, Type3, _>(|n| {
+            if n % 2 == 0 {
+                Either::Left(n as u64)
+            } else {
+                Either::Right(n.to_string())
+            }
+        });
+
+        assert_eq!(evens.capacity(), 6);
+        assert_eq!(evens.into_vec(), vec![0, 2, 4]);
+        assert_eq!(odds.into_vec(), vec!["1", "3", "5"]);
+    }
+
+    #[test]
+    fn test_union_vec_flat_map_in_place() {
+        let mut union_vec = UnionVec::<u32, (u32, u64)>::with_capacity(4);
+
+        for n in 0..4 {
+            union_vec.push(n);
+        }
+
+        // The first element expands, but the rest shrinks, so everything fits in place.
+        let union_vec = union_vec.flat_map::<Type2, _, _>(|n| match n {
+            0 => vec![0, 0, 0],
+            3 => vec![3],
+            _ => vec![],
+        });
+
+        assert_eq!(union_vec.capacity(), 4);
+        assert_eq!(union_vec.into_vec(), vec![0, 0, 0, 3]);
+    }
+
+    #[test]
+    fn test_union_vec_flat_map_spills() {
+        let mut union_vec = UnionVec::<u32, (u32, String)>::new();
+
+        for n in 1..4 {
+            union_vec.push(n);
+        }
+
+        let union_vec =
+            union_vec.flat_map::<Type2, _, _>(|n| (0..n).map(move |i| format!("{}.{}", n, i)));
+
+        assert_eq!(
+            union_vec.into_vec(),
+            vec!["1.0", "2.0", "2.1", "3.0", "3.1", "3.2"]
+        );
+    }
+
+    #[test]
+    fn test_union_vec_zip_map_self_longer() {
+        let mut names = UnionVec::<String, (String, u64)>::new();
+
+        for s in ["a", "bb", "ccc"] {
+            names.push(String::from(s));
+        }
+
+        let mut factors = UnionVec::<u64, (u64, u32)>::new();
+        factors.push(10);
+        factors.push(100);
+
+        let (products, leftover) =
+            names.zip_map::<Type2, _, _, _>(factors, |s, n| s.len() as u64 * n);
+
+        assert_eq!(products.into_vec(), vec![10, 200]);
+
+        match leftover {
+            Some(Either::Left(names)) => assert_eq!(names.into_vec(), vec!["ccc"]),
+            _ => panic!("names was longer"),
+        }
+    }
+
+    #[test]
+    fn test_union_vec_fmt() {
+        let mut union_vec = UnionVec::<u16, (u16, u64)>::with_capacity(4);
+        union_vec.push(1);
+        union_vec.push(2);
+
+        assert_eq!(format!("{}", union_vec), "[1, 2]");
+        assert_eq!(
+            format!("{:#?}", union_vec),
+            "UnionVec<u16> {\n    capacity: 4,\n    union_size: 8,\n    union_align: 8,\n    \
+             members: [\n        \"u16\",\n        \"u64\",\n    ],\n    \
+             elements: [\n        1,\n        2,\n    ],\n}"
+        );
+    }
+
+    #[test]
+    fn test_union_vec_borrowed_members() {
+        use std::borrow::Cow;
+
+        let input = String::from("a bb ccc");
+
+        let mut words = UnionVec::<&str, (&str, Cow<str>, usize)>::new();
+
+        for word in input.split(' ') {
+            words.push(word);
+        }
+
+        let words = words.map::<Type2, _>(|word| match word.len() {
+            1 => Cow::Borrowed(word),
+            _ => Cow::Owned(word.to_uppercase()),
+        });
+
+        let owned = words.map::<Type3, _>(|word| match word {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(word) => word.len(),
+        });
+
+        assert_eq!(owned.into_vec(), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn test_union_vec_try_convert_drops_on_error() {
+        use std::convert::TryFrom;
+        use std::rc::Rc;
+
+        struct Counted(Rc<()>, bool);
+
+        impl TryFrom<Counted> for (Rc<()>,) {
+            type Error = ();
+
+            fn try_from(c: Counted) -> Result<Self, ()> {
+                if c.1 {
+                    Ok((c.0,))
+                } else {
+                    Err(())
+                }
+            }
+        }
+
+        let rc = Rc::new(());
+
+        let mut union_vec = UnionVec::<Counted, (Counted, (Rc<()>,))>::new();
+
+        for ok in [true, false, true] {
+            union_vec.push(Counted(Rc::clone(&rc), ok));
+        }
+
+        assert!(union_vec.try_convert::<Type2>().is_err());
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn test_union_vec_filter_map_drops_on_panic() {
+        use std::cell::Cell;
+        use std::panic::{self, AssertUnwindSafe};
+        use std::rc::Rc;
+
+        let rc = Rc::new(());
+
+        let mut union_vec = UnionVec::<Rc<()>, (Rc<()>, Option<Rc<()>>)>::new();
+
+        for _ in 0..4 {
+            union_vec.push(Rc::clone(&rc));
+        }
+
+        let seen = Cell::new(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            union_vec.filter_map::<Type2, _>(|rc| {
+                seen.set(seen.get() + 1);
+                assert!(seen.get() < 3);
+                Some(Some(rc))
+            })
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn test_union_vec_raw_parts() {
+        let mut union_vec = UnionVec::<String, (String, u64)>::with_capacity(4);
+        union_vec.push(String::from("raw"));
+
+        let (ptr, len, capacity) = union_vec.into_raw_parts();
+        assert_eq!((len, capacity), (1, 4));
+
+        let mut union_vec =
+            unsafe { UnionVec::<String, (String, u64)>::from_raw_parts(ptr, len, capacity) };
+        assert_eq!(union_vec.as_ptr(), ptr as *const _);
+        assert_eq!(union_vec.pop(), Some(String::from("raw")));
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    #[should_panic(expected = "does not hold the type it is accessed as")]
+    fn test_union_vec_checked_transmute() {
+        let mut union_vec = UnionVec::<u32, (u32, u64)>::new();
+        union_vec.push(1);
+
+        let mut union_vec: UnionVec<u64, (u32, u64)> = unsafe { mem::transmute(union_vec) };
+
+        union_vec.pop();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic]
+    fn test_cast_checked_non_member() {
+        let union = SelectHandle::<u32, (u32, u64)>::from(1).into_inner();
+
+        unsafe { union.cast_checked::<i32>() };
+    }
+
+    #[test]
+    fn test_union_vec_drops_elements_as_t() {
+        use std::rc::Rc;
+
+        let rc = Rc::new(());
+
+        let mut union_vec = UnionVec::<Rc<()>, (Rc<()>, u64)>::new();
+
+        for _ in 0..3 {
+            union_vec.push(Rc::clone(&rc));
+        }
+
+        union_vec.clear();
+        assert_eq!(Rc::strong_count(&rc), 1);
+        assert_eq!(union_vec.len(), 0);
+
+        union_vec.push(Rc::clone(&rc));
+        let mut union_vec = union_vec.change_to::<Type1>();
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        union_vec.push(Rc::clone(&rc));
+        drop(union_vec);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
 }
@@ -0,0 +1,38 @@
+//! The tag of the `checked` feature.
+//!
+//! Every union-vector carries the `TypeId` of the type it was created with. Every typed accessor
+//! compares it to the `TypeId` of the type it is accessed as, so a union-vector that was
+//! transmuted or cast to another current type fails loudly, instead of reading garbage.
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+use std::mem;
+
+/// Gives the `TypeId` of `T`, through a trait object of which the lifetime can be erased.
+trait TypeTag {
+    fn type_id(&self) -> TypeId
+    where
+        Self: 'static;
+}
+
+impl<T: ?Sized> TypeTag for PhantomData<T> {
+    #[inline]
+    fn type_id(&self) -> TypeId
+    where
+        Self: 'static,
+    {
+        TypeId::of::<T>()
+    }
+}
+
+/// Returns the `TypeId` of `T`, ignoring its lifetimes. Types that only differ in their lifetimes
+/// therefore get the same tag.
+#[inline]
+pub(super) fn tag_of<T: ?Sized>() -> TypeId {
+    let marker = PhantomData::<T>;
+    let tag: &dyn TypeTag = &marker;
+
+    // `TypeId::of` does not depend on lifetimes, so erasing them can not give a different id.
+    let tag: &(dyn TypeTag + 'static) = unsafe { mem::transmute(tag) };
+    tag.type_id()
+}
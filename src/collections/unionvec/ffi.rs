@@ -0,0 +1,90 @@
+//! A C-compatible view of a union-vector, for Unions with a C-compatible layout.
+
+use std::marker::PhantomData;
+use std::mem;
+
+use super::UnionVec;
+use select::ReprC;
+
+/// A C-compatible view of the elements of a union-vector.
+///
+/// The `i`th element is a `T` of `elem_size` bytes, at `ptr + i * stride` bytes. The stride is
+/// the size of the Union, and is at least `elem_size`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct FfiSlice<'a, T> {
+    /// A pointer to the first element.
+    pub ptr: *const T,
+
+    /// The number of elements.
+    pub len: usize,
+
+    /// The distance between two elements, in bytes.
+    pub stride: usize,
+
+    /// The size of an element, in bytes.
+    pub elem_size: usize,
+
+    marker: PhantomData<&'a [T]>,
+}
+
+impl<T, U: ReprC> UnionVec<T, U> {
+    /// Returns a C-compatible view of the elements, which can be read in place by C code.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::type_union;
+    ///
+    /// type_union! {
+    ///     #[repr(C)]
+    ///     union Pixel {
+    ///         Rgba: [u8; 4],
+    ///         Float: [f32; 4]
+    ///     }
+    /// }
+    ///
+    /// let mut union_vec = UnionVec::<[u8; 4], Pixel>::new();
+    /// union_vec.push([255, 0, 0, 255]);
+    /// union_vec.push([0, 255, 0, 255]);
+    ///
+    /// let view = union_vec.as_ffi_slice();
+    ///
+    /// assert_eq!((view.len, view.stride, view.elem_size), (2, 16, 4));
+    ///
+    /// let second = unsafe { *(view.ptr as *const u8).add(view.stride + 1) };
+    /// assert_eq!(second, 255);
+    /// ```
+    #[inline]
+    pub fn as_ffi_slice(&self) -> FfiSlice<'_, T> {
+        self.check_tag();
+
+        FfiSlice {
+            ptr: self.data.as_ptr() as *const T,
+            len: self.data.len(),
+            stride: U::SIZE,
+            elem_size: mem::size_of::<T>(),
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "repr-c"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_slice_of_tuple_union() {
+        let mut union_vec = UnionVec::<u16, (u16, u64)>::new();
+        union_vec.push(1);
+        union_vec.push(2);
+
+        let view = union_vec.as_ffi_slice();
+        assert_eq!((view.len, view.stride, view.elem_size), (2, 8, 2));
+
+        let second = unsafe { *((view.ptr as *const u8).add(view.stride) as *const u16) };
+        assert_eq!(second, 2);
+    }
+}
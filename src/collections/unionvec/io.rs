@@ -0,0 +1,226 @@
+//! Byte-level io for a union-vector that currently holds `u8`'s.
+
+use std::cmp;
+use std::io;
+use std::ptr;
+use std::slice;
+
+use super::UnionVec;
+use select::TypeUnion;
+
+/// The number of bytes a [`UnionVecReader`] gathers at once, when the bytes are not contiguous.
+const BUF_SIZE: usize = 256;
+
+/// Appends the bytes to the union-vector.
+impl<U: TypeUnion> io::Write for UnionVec<u8, U> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_tag();
+        self.data.reserve(buf.len());
+
+        // The bytes are contiguous when the Union is a single byte, so they are copied at once.
+        if U::SIZE == 1 {
+            let len = self.data.len();
+
+            unsafe {
+                let dst = self.data.as_mut_ptr().add(len) as *mut u8;
+                ptr::copy_nonoverlapping(buf.as_ptr(), dst, buf.len());
+                self.data.set_len(len + buf.len());
+            }
+        } else {
+            for &byte in buf {
+                self.push(byte);
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<U: TypeUnion> UnionVec<u8, U> {
+    /// Returns a cursor over the bytes of the union-vector, implementing [`io::Read`] and
+    /// [`io::BufRead`].
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use std::io::{BufRead, Write};
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let mut union_vec = UnionVec::<u8, (u8, u64)>::new();
+    /// union_vec.write_all(b"first\nsecond\n").unwrap();
+    ///
+    /// let lines: Vec<String> = union_vec.reader().lines().map(|l| l.unwrap()).collect();
+    ///
+    /// assert_eq!(lines, vec!["first", "second"]);
+    /// ```
+    #[inline]
+    pub fn reader(&self) -> UnionVecReader<'_, U> {
+        self.check_tag();
+
+        UnionVecReader {
+            union_vec: self,
+            pos: 0,
+            buf: [0; BUF_SIZE],
+            buf_start: 0,
+            buf_len: 0,
+        }
+    }
+}
+
+/// A cursor over the bytes of a [`UnionVec`], see [`UnionVec::reader`].
+///
+/// When the Union is bigger than a byte, the bytes are not laid out contiguous. The cursor then
+/// gathers them into a buffer of its own for [`io::BufRead::fill_buf`]. Otherwise the bytes are
+/// handed out in place.
+pub struct UnionVecReader<'a, U: TypeUnion + 'a> {
+    union_vec: &'a UnionVec<u8, U>,
+    pos: usize,
+
+    /// The bytes from `buf_start` to `buf_start + buf_len`, gathered for `fill_buf`.
+    buf: [u8; BUF_SIZE],
+    buf_start: usize,
+    buf_len: usize,
+}
+
+impl<'a, U: TypeUnion> UnionVecReader<'a, U> {
+    /// Returns the position of the cursor, in bytes.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.union_vec.len() - self.pos
+    }
+
+    #[inline]
+    fn byte_ptr(&self, index: usize) -> *const u8 {
+        unsafe { self.union_vec.data.as_ptr().add(index) as *const u8 }
+    }
+
+    /// Copies the bytes from `pos` on into `buf`, one from every Union.
+    #[inline]
+    fn gather(&self, pos: usize, buf: &mut [u8]) {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = unsafe { *self.byte_ptr(pos + i) };
+        }
+    }
+}
+
+impl<'a, U: TypeUnion> io::Read for UnionVecReader<'a, U> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = cmp::min(buf.len(), self.remaining());
+
+        self.gather(self.pos, &mut buf[..n]);
+
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl<'a, U: TypeUnion> io::BufRead for UnionVecReader<'a, U> {
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let remaining = self.remaining();
+
+        if U::SIZE == 1 || remaining == 0 {
+            return Ok(unsafe { slice::from_raw_parts(self.byte_ptr(self.pos), remaining) });
+        }
+
+        if self.pos < self.buf_start || self.pos >= self.buf_start + self.buf_len {
+            let n = cmp::min(BUF_SIZE, remaining);
+            let mut buf = [0; BUF_SIZE];
+
+            self.gather(self.pos, &mut buf[..n]);
+
+            self.buf = buf;
+            self.buf_start = self.pos;
+            self.buf_len = n;
+        }
+
+        Ok(&self.buf[self.pos - self.buf_start..self.buf_len])
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.pos += cmp::min(amt, self.remaining());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use index::Type2;
+    use std::io::{BufRead, Read, Write};
+
+    #[test]
+    fn test_write_then_map() {
+        let mut union_vec = UnionVec::<u8, (u8, u32)>::with_capacity(16);
+        union_vec.write_all(&[1, 2, 3, 4]).unwrap();
+
+        let mut union_vec = union_vec.map::<Type2, _>(|b| b as u32 * 1000);
+
+        assert_eq!(union_vec.capacity(), 16);
+        assert_eq!(union_vec.pop(), Some(4000));
+    }
+
+    #[test]
+    fn test_read_strided() {
+        let mut union_vec = UnionVec::<u8, (u8, u64)>::new();
+        union_vec.write_all(b"hello world").unwrap();
+
+        let mut reader = union_vec.reader();
+        let mut hello = [0; 5];
+
+        reader.read_exact(&mut hello).unwrap();
+        assert_eq!(&hello, b"hello");
+        assert_eq!(reader.fill_buf().unwrap(), b" world");
+
+        reader.consume(1);
+        assert_eq!(reader.fill_buf().unwrap(), b"world");
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"world");
+    }
+
+    #[test]
+    fn test_fill_buf_contiguous() {
+        let mut union_vec = UnionVec::<u8, (u8, i8)>::new();
+        union_vec.write_all(b"bytes").unwrap();
+
+        let mut reader = union_vec.reader();
+        assert_eq!(reader.fill_buf().unwrap(), b"bytes");
+
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().unwrap(), b"tes");
+        assert_eq!(reader.position(), 2);
+    }
+
+    #[test]
+    fn test_fill_buf_strided_refills() {
+        let bytes: Vec<u8> = (0..BUF_SIZE + 10).map(|i| i as u8).collect();
+
+        let mut union_vec = UnionVec::<u8, (u8, u32)>::new();
+        union_vec.write_all(&bytes).unwrap();
+
+        let mut reader = union_vec.reader();
+        assert_eq!(reader.fill_buf().unwrap(), &bytes[..BUF_SIZE]);
+
+        reader.consume(BUF_SIZE - 1);
+        assert_eq!(reader.fill_buf().unwrap(), &bytes[BUF_SIZE - 1..BUF_SIZE]);
+
+        reader.consume(1);
+        assert_eq!(reader.fill_buf().unwrap(), &bytes[BUF_SIZE..]);
+    }
+}
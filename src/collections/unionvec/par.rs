@@ -0,0 +1,367 @@
+//! Parallel conversions of a [`UnionVec`], which split the underlying Vec into disjoint chunks.
+
+use std::cmp;
+use std::marker::PhantomData;
+use std::mem;
+use std::panic;
+use std::ptr;
+use std::thread;
+
+use super::UnionVec;
+use select::{Select, Selector, TypeUnion};
+
+/// A pointer into the underlying Vec, that can be send to other threads.
+/// Every thread only ever touches its own chunk behind the pointer.
+struct SendPtr<X>(*mut X);
+
+impl<X> Clone for SendPtr<X> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<X> Copy for SendPtr<X> {}
+
+unsafe impl<X> Send for SendPtr<X> {}
+
+/// Cleans up a chunk when the closure panics.
+/// The first `written` elements of the chunk hold an `O`, the element at `read` was moved into
+/// the closure, and every element after it still holds a `T`.
+struct ChunkGuard<T, O, X> {
+    ptr: *mut X,
+    len: usize,
+    read: usize,
+    written: usize,
+    marker: PhantomData<(T, O)>,
+}
+
+impl<T, O, X> Drop for ChunkGuard<T, O, X> {
+    fn drop(&mut self) {
+        unsafe {
+            for i in 0..self.written {
+                ptr::drop_in_place(self.ptr.add(i) as *mut O);
+            }
+
+            for i in self.read + 1..self.len {
+                ptr::drop_in_place(self.ptr.add(i) as *mut T);
+            }
+        }
+    }
+}
+
+/// Calls the closure for every element of the chunk, and keeps the `Some`'s at the front of the
+/// chunk. Returns the number of elements kept.
+unsafe fn filter_map_chunk<T, O, X, F>(ptr: *mut X, len: usize, f: &F) -> usize
+where
+    F: Fn(T) -> Option<O>,
+{
+    let mut guard = ChunkGuard::<T, O, X> {
+        ptr,
+        len,
+        read: 0,
+        written: 0,
+        marker: PhantomData,
+    };
+
+    while guard.read < len {
+        let t = ptr::read(ptr.add(guard.read) as *mut T);
+
+        if let Some(o) = f(t) {
+            ptr::write(ptr.add(guard.written) as *mut O, o);
+            guard.written += 1;
+        }
+
+        guard.read += 1;
+    }
+
+    let written = guard.written;
+    mem::forget(guard);
+    written
+}
+
+/// Returns the number of threads to use when none is given.
+fn default_threads() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+impl<T, U: TypeUnion> UnionVec<T, U> {
+    /// Like [`UnionVec::map`], but the elements are mapped on multiple threads. Every thread maps
+    /// a disjoint chunk of the underlying Vec, in place.
+    ///
+    /// The number of threads is the available parallelism of the system, see
+    /// [`UnionVec::par_map_with_threads`] to choose it.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, String)>::new();
+    ///
+    /// for n in 0..1000 {
+    ///     union_vec.push(n);
+    /// }
+    ///
+    /// let mut union_vec = union_vec.par_map::<Type2, _>(|n| n.to_string());
+    ///
+    /// assert_eq!(union_vec.len(), 1000);
+    /// assert_eq!(union_vec.pop(), Some(String::from("999")));
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// When the closure panics on any thread, all elements are dropped as the type they hold at
+    /// that point, the underlying Vec is freed, and the panic is resumed on the calling thread.
+    #[inline]
+    pub fn par_map<S, F>(self, f: F) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>,
+        T: Send,
+        <U as Select<S>>::Output: Send,
+        F: Fn(T) -> <U as Select<S>>::Output + Sync,
+    {
+        self.par_map_with_threads::<S, F>(default_threads(), f)
+    }
+
+    /// Like [`UnionVec::par_map`], using at most `threads` threads.
+    ///
+    /// # Panic
+    ///
+    /// See [`UnionVec::par_map`].
+    #[inline]
+    pub fn par_map_with_threads<S, F>(
+        self,
+        threads: usize,
+        f: F,
+    ) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>,
+        T: Send,
+        <U as Select<S>>::Output: Send,
+        F: Fn(T) -> <U as Select<S>>::Output + Sync,
+    {
+        // A map is a filter_map that keeps every element, so no chunk ever has to be moved.
+        self.par_filter_map_with_threads::<S, _>(threads, |t| Some(f(t)))
+    }
+
+    /// Like [`UnionVec::filter_map`], but the elements are mapped on multiple threads. Every
+    /// thread compacts the `Some`'s of a disjoint chunk of the underlying Vec to the front of that
+    /// chunk, after which the chunks are stitched together.
+    ///
+    /// The number of threads is the available parallelism of the system, see
+    /// [`UnionVec::par_filter_map_with_threads`] to choose it.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    /// use unioncollections::index::Type2;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, u64)>::new();
+    ///
+    /// for n in 0..1000 {
+    ///     union_vec.push(n);
+    /// }
+    ///
+    /// let mut union_vec = union_vec.par_filter_map::<Type2, _>(|n| {
+    ///     if n % 2 == 0 { Some(n as u64) } else { None }
+    /// });
+    ///
+    /// assert_eq!(union_vec.len(), 500);
+    /// assert_eq!(union_vec.pop(), Some(998));
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// See [`UnionVec::par_map`].
+    #[inline]
+    pub fn par_filter_map<S, F>(self, f: F) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>,
+        T: Send,
+        <U as Select<S>>::Output: Send,
+        F: Fn(T) -> Option<<U as Select<S>>::Output> + Sync,
+    {
+        self.par_filter_map_with_threads::<S, F>(default_threads(), f)
+    }
+
+    /// Like [`UnionVec::par_filter_map`], using at most `threads` threads.
+    ///
+    /// # Panic
+    ///
+    /// See [`UnionVec::par_map`].
+    pub fn par_filter_map_with_threads<S, F>(
+        self,
+        threads: usize,
+        f: F,
+    ) -> UnionVec<<U as Select<S>>::Output, U>
+    where
+        S: Selector,
+        U: Select<S>,
+        T: Send,
+        <U as Select<S>>::Output: Send,
+        F: Fn(T) -> Option<<U as Select<S>>::Output> + Sync,
+    {
+        let mut data = self.take_data();
+        let len = data.len();
+
+        let threads = cmp::max(1, cmp::min(threads, len));
+        let chunk_len = len.div_ceil(threads);
+        let chunks: Vec<(usize, usize)> = (0..threads)
+            .map(|i| (i * chunk_len, cmp::min((i + 1) * chunk_len, len)))
+            .filter(|&(start, end)| start < end)
+            .collect();
+
+        unsafe {
+            data.set_len(0);
+        }
+
+        let ptr = SendPtr(data.as_mut_ptr());
+        let f = &f;
+
+        let results: Vec<thread::Result<usize>> = thread::scope(|scope| {
+            let handles: Vec<_> = chunks
+                .iter()
+                .map(|&(start, end)| {
+                    scope.spawn(move || unsafe {
+                        filter_map_chunk::<T, <U as Select<S>>::Output, U::Union, F>(
+                            ptr.0.add(start),
+                            end - start,
+                            f,
+                        )
+                    })
+                })
+                .collect();
+
+            handles.into_iter().map(|handle| handle.join()).collect()
+        });
+
+        if results.iter().any(|result| result.is_err()) {
+            let mut payload = None;
+
+            for (&(start, _), result) in chunks.iter().zip(results) {
+                match result {
+                    Ok(written) => unsafe {
+                        for i in start..start + written {
+                            ptr::drop_in_place(ptr.0.add(i) as *mut <U as Select<S>>::Output);
+                        }
+                    },
+                    Err(e) => {
+                        payload.get_or_insert(e);
+                    }
+                }
+            }
+
+            drop(data);
+            panic::resume_unwind(payload.unwrap());
+        }
+
+        let mut new_len = 0;
+
+        for (&(start, _), result) in chunks.iter().zip(results) {
+            let written = result.unwrap_or(0);
+
+            unsafe {
+                ptr::copy(ptr.0.add(start), ptr.0.add(new_len), written);
+            }
+
+            new_len += written;
+        }
+
+        unsafe {
+            data.set_len(new_len);
+        }
+
+        unsafe { UnionVec::from_data_unchecked(data) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use index::Type2;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted(u32);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_par_map_threads() {
+        let mut union_vec = UnionVec::<u32, (u32, u64)>::new();
+
+        for n in 0..10_000 {
+            union_vec.push(n);
+        }
+
+        let mut union_vec = union_vec.par_map_with_threads::<Type2, _>(7, |n| n as u64 * 2);
+
+        assert_eq!(union_vec.len(), 10_000);
+
+        for n in (0..10_000).rev() {
+            assert_eq!(union_vec.pop(), Some(n * 2));
+        }
+    }
+
+    #[test]
+    fn test_par_filter_map_stitches_chunks() {
+        let mut union_vec = UnionVec::<u32, (u32, String)>::new();
+
+        for n in 0..1000 {
+            union_vec.push(n);
+        }
+
+        let mut union_vec = union_vec.par_filter_map_with_threads::<Type2, _>(4, |n| {
+            if n % 3 == 0 {
+                Some(n.to_string())
+            } else {
+                None
+            }
+        });
+
+        assert_eq!(union_vec.len(), 334);
+
+        for n in (0..1000).rev().filter(|n| n % 3 == 0) {
+            assert_eq!(union_vec.pop(), Some(n.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_par_map_panic_drops_all() {
+        let mut union_vec = UnionVec::<Counted, (Counted, String)>::new();
+
+        for n in 0..1000 {
+            union_vec.push(Counted(n));
+        }
+
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(move || {
+            union_vec.par_map_with_threads::<Type2, _>(4, |c| {
+                if c.0 == 600 {
+                    panic!("worker panic");
+                }
+
+                c.0.to_string()
+            })
+        }));
+
+        assert!(result.is_err());
+
+        // Every `Counted` is dropped exactly once: either by the closure, or during cleanup.
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1000);
+    }
+}
@@ -0,0 +1,388 @@
+//! Serde support for [`UnionVec`], which is (de)serialized as a sequence of its current type.
+
+use std::cmp;
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use super::UnionVec;
+use select::TypeUnion;
+
+/// The maximum number of elements preallocated from a size hint, so a hostile size hint can not
+/// cause a huge allocation.
+const MAX_PREALLOCATE: usize = 4096;
+
+impl<T, U> Serialize for UnionVec<T, U>
+where
+    T: Serialize,
+    U: TypeUnion,
+{
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.check_tag();
+
+        serializer.collect_seq(
+            self.data
+                .iter()
+                .map(|union| unsafe { &*(union as *const U::Union as *const T) }),
+        )
+    }
+}
+
+struct UnionVecVisitor<T, U>(PhantomData<(T, U)>);
+
+impl<'de, T, U> Visitor<'de> for UnionVecVisitor<T, U>
+where
+    T: Deserialize<'de>,
+    U: TypeUnion,
+{
+    type Value = UnionVec<T, U>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let capacity = cmp::min(seq.size_hint().unwrap_or(0), MAX_PREALLOCATE);
+        let mut union_vec = UnionVec::with_capacity(capacity);
+
+        while let Some(item) = seq.next_element()? {
+            union_vec.push(item);
+        }
+
+        Ok(union_vec)
+    }
+}
+
+impl<'de, T, U> Deserialize<'de> for UnionVec<T, U>
+where
+    T: Deserialize<'de>,
+    U: TypeUnion,
+{
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(UnionVecVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use index::Type2;
+    use select::SelectHandle;
+    use serde::de::{self, IntoDeserializer};
+    use serde::forward_to_deserialize_any;
+    use serde::ser::{self, Impossible, SerializeSeq};
+    use std::error;
+    use std::vec;
+
+    /// A minimal in-memory format, that only knows about integers, strings and sequences.
+    #[derive(Debug, Clone, PartialEq)]
+    enum Value {
+        U64(u64),
+        Str(String),
+        Seq(Vec<Value>),
+    }
+
+    #[derive(Debug)]
+    struct Error(String);
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl error::Error for Error {}
+
+    impl ser::Error for Error {
+        fn custom<M: fmt::Display>(msg: M) -> Self {
+            Error(msg.to_string())
+        }
+    }
+
+    impl de::Error for Error {
+        fn custom<M: fmt::Display>(msg: M) -> Self {
+            Error(msg.to_string())
+        }
+    }
+
+    fn unsupported<T>() -> Result<T, Error> {
+        Err(Error(String::from("unsupported by the test format")))
+    }
+
+    struct ValueSerializer;
+
+    struct SeqSerializer(Vec<Value>);
+
+    impl SerializeSeq for SeqSerializer {
+        type Ok = Value;
+        type Error = Error;
+
+        fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+            self.0.push(value.serialize(ValueSerializer)?);
+            Ok(())
+        }
+
+        fn end(self) -> Result<Value, Error> {
+            Ok(Value::Seq(self.0))
+        }
+    }
+
+    impl Serializer for ValueSerializer {
+        type Ok = Value;
+        type Error = Error;
+        type SerializeSeq = SeqSerializer;
+        type SerializeTuple = Impossible<Value, Error>;
+        type SerializeTupleStruct = Impossible<Value, Error>;
+        type SerializeTupleVariant = Impossible<Value, Error>;
+        type SerializeMap = Impossible<Value, Error>;
+        type SerializeStruct = Impossible<Value, Error>;
+        type SerializeStructVariant = Impossible<Value, Error>;
+
+        fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+            self.serialize_u64(v as u64)
+        }
+
+        fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+            self.serialize_u64(v as u64)
+        }
+
+        fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+            self.serialize_u64(v as u64)
+        }
+
+        fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+            Ok(Value::U64(v))
+        }
+
+        fn serialize_str(self, v: &str) -> Result<Value, Error> {
+            Ok(Value::Str(v.to_string()))
+        }
+
+        fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+            Ok(SeqSerializer(Vec::with_capacity(len.unwrap_or(0))))
+        }
+
+        fn serialize_bool(self, _: bool) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_i8(self, _: i8) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_i16(self, _: i16) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_i32(self, _: i32) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_i64(self, _: i64) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_f32(self, _: f32) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_f64(self, _: f64) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_char(self, _: char) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_bytes(self, _: &[u8]) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_none(self) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_unit(self) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_unit_struct(self, _: &'static str) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_unit_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+        ) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_newtype_struct<T: ?Sized + Serialize>(
+            self,
+            _: &'static str,
+            _: &T,
+        ) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_newtype_variant<T: ?Sized + Serialize>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> Result<Value, Error> {
+            unsupported()
+        }
+
+        fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Error> {
+            unsupported()
+        }
+
+        fn serialize_tuple_struct(
+            self,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeTupleStruct, Error> {
+            unsupported()
+        }
+
+        fn serialize_tuple_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeTupleVariant, Error> {
+            unsupported()
+        }
+
+        fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+            unsupported()
+        }
+
+        fn serialize_struct(
+            self,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeStruct, Error> {
+            unsupported()
+        }
+
+        fn serialize_struct_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeStructVariant, Error> {
+            unsupported()
+        }
+    }
+
+    struct SeqDeserializer(vec::IntoIter<Value>);
+
+    impl<'de> SeqAccess<'de> for SeqDeserializer {
+        type Error = Error;
+
+        fn next_element_seed<S>(&mut self, seed: S) -> Result<Option<S::Value>, Error>
+        where
+            S: de::DeserializeSeed<'de>,
+        {
+            match self.0.next() {
+                Some(value) => seed.deserialize(value).map(Some),
+                None => Ok(None),
+            }
+        }
+
+        fn size_hint(&self) -> Option<usize> {
+            Some(self.0.len())
+        }
+    }
+
+    impl<'de> IntoDeserializer<'de, Error> for Value {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    impl<'de> Deserializer<'de> for Value {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                Value::U64(n) => visitor.visit_u64(n),
+                Value::Str(s) => visitor.visit_string(s),
+                Value::Seq(items) => visitor.visit_seq(SeqDeserializer(items.into_iter())),
+            }
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    #[test]
+    fn test_serialize_current_type() {
+        let mut union_vec = UnionVec::<u64, (u64, String)>::new();
+        union_vec.push(1);
+        union_vec.push(2);
+
+        let value = union_vec.serialize(ValueSerializer).unwrap();
+        assert_eq!(value, Value::Seq(vec![Value::U64(1), Value::U64(2)]));
+
+        let union_vec = union_vec.map::<Type2, _>(|n| n.to_string());
+        let value = union_vec.serialize(ValueSerializer).unwrap();
+
+        assert_eq!(
+            value,
+            Value::Seq(vec![
+                Value::Str(String::from("1")),
+                Value::Str(String::from("2")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_deserialize_preallocates() {
+        let value = Value::Seq(vec![
+            Value::Str(String::from("a")),
+            Value::Str(String::from("b")),
+            Value::Str(String::from("c")),
+        ]);
+
+        let mut union_vec = UnionVec::<String, (u64, String)>::deserialize(value).unwrap();
+
+        assert_eq!(union_vec.len(), 3);
+        assert_eq!(union_vec.capacity(), 3);
+        assert_eq!(union_vec.pop(), Some(String::from("c")));
+    }
+
+    #[test]
+    fn test_deserialize_wrong_type() {
+        let value = Value::Seq(vec![Value::U64(1)]);
+
+        assert!(UnionVec::<String, (u64, String)>::deserialize(value).is_err());
+    }
+
+    #[test]
+    fn test_serialize_select_handle() {
+        let handle = SelectHandle::<u64, (u64, String)>::from(10);
+
+        assert_eq!(handle.serialize(ValueSerializer).unwrap(), Value::U64(10));
+    }
+}
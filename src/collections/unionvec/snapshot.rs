@@ -0,0 +1,395 @@
+//! A compact binary snapshot format for union-vectors of which every type is [`Pod`].
+//!
+//! A snapshot starts with a header that fingerprints the [`TypeUnion`]: the name, size and
+//! alignment of every type of the Union, and the index of the current type. The elements follow,
+//! as the raw bytes of the current type, in native byte order.
+//!
+//! The fingerprint compares the names reported by [`std::any::type_name`], which are not stable
+//! across compiler versions. A snapshot is therefore only guaranteed to be readable by the same
+//! build of the program that wrote it.
+
+use std::cmp;
+use std::error;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::slice;
+
+use super::UnionVec;
+use pod::{Pod, PodUnion};
+use select::TypeUnion;
+
+const MAGIC: [u8; 4] = *b"UVSN";
+
+/// The maximum number of bytes that are allocated up front when reading a snapshot. The length in
+/// the header is not trusted, larger buffers grow as the elements are read.
+const MAX_PREALLOCATE: usize = 64 * 1024;
+const VERSION: u8 = 1;
+
+#[cfg(target_endian = "little")]
+const ENDIAN: u8 = 0;
+#[cfg(target_endian = "big")]
+const ENDIAN: u8 = 1;
+
+/// The error returned when a snapshot can not be read.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading from the underlying reader failed.
+    Io(io::Error),
+
+    /// The data does not start with the snapshot magic.
+    BadMagic,
+
+    /// The snapshot was written with an unsupported version of the format.
+    UnsupportedVersion(u8),
+
+    /// The snapshot was written on a machine with a different byte order.
+    EndianMismatch,
+
+    /// The types of the Union in the snapshot differ from the types of the Union read into.
+    UnionMismatch,
+
+    /// The snapshot holds a different type of the Union than the type read into.
+    SelectorMismatch {
+        /// The index of the type read into.
+        expected: usize,
+
+        /// The index of the type in the snapshot.
+        found: usize,
+    },
+
+    /// The number of elements in the snapshot does not fit in memory.
+    TooLarge,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SnapshotError::Io(ref e) => write!(f, "failed to read snapshot: {}", e),
+            SnapshotError::BadMagic => f.write_str("not a union-vector snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot version {}", v)
+            }
+            SnapshotError::EndianMismatch => {
+                f.write_str("snapshot was written with a different byte order")
+            }
+            SnapshotError::UnionMismatch => {
+                f.write_str("snapshot was written with a different union")
+            }
+            SnapshotError::SelectorMismatch { expected, found } => write!(
+                f,
+                "snapshot holds type {} of the union, expected type {}",
+                found, expected
+            ),
+            SnapshotError::TooLarge => f.write_str("snapshot is too large"),
+        }
+    }
+}
+
+impl error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            SnapshotError::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    #[inline]
+    fn from(e: io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+/// Returns the index of `T` in `U`.
+///
+/// # Panic
+///
+/// When `T` is not a type of `U`, this function will panic.
+fn selector_index<T: 'static, U: TypeUnion + 'static>() -> usize {
+    U::index_of::<T>().expect("The current type is not a type of the union")
+}
+
+fn write_u32<W: io::Write>(w: &mut W, n: u32) -> io::Result<()> {
+    w.write_all(&n.to_le_bytes())
+}
+
+fn write_u64<W: io::Write>(w: &mut W, n: u64) -> io::Result<()> {
+    w.write_all(&n.to_le_bytes())
+}
+
+fn read_u8<R: io::Read>(r: &mut R) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32<R: io::Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: io::Read>(r: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Writes the fingerprint of `U`.
+fn write_fingerprint<U: TypeUnion, W: io::Write>(w: &mut W) -> io::Result<()> {
+    let names = U::member_type_names();
+    let layouts = U::member_layouts();
+
+    write_u32(w, names.len() as u32)?;
+
+    for (name, layout) in names.iter().zip(layouts) {
+        write_u32(w, name.len() as u32)?;
+        w.write_all(name.as_bytes())?;
+        write_u64(w, layout.size() as u64)?;
+        write_u64(w, layout.align() as u64)?;
+    }
+
+    write_u64(w, U::SIZE as u64)?;
+    write_u64(w, U::ALIGN as u64)
+}
+
+/// Reads a fingerprint, and checks it against the fingerprint of `U`.
+fn check_fingerprint<U: TypeUnion, R: io::Read>(r: &mut R) -> Result<(), SnapshotError> {
+    let names = U::member_type_names();
+    let layouts = U::member_layouts();
+
+    if read_u32(r)? as usize != names.len() {
+        return Err(SnapshotError::UnionMismatch);
+    }
+
+    for (name, layout) in names.iter().zip(layouts) {
+        if read_u32(r)? as usize != name.len() {
+            return Err(SnapshotError::UnionMismatch);
+        }
+
+        let mut buf = vec![0; name.len()];
+        r.read_exact(&mut buf)?;
+
+        if buf != name.as_bytes()
+            || read_u64(r)? != layout.size() as u64
+            || read_u64(r)? != layout.align() as u64
+        {
+            return Err(SnapshotError::UnionMismatch);
+        }
+    }
+
+    if read_u64(r)? != U::SIZE as u64 || read_u64(r)? != U::ALIGN as u64 {
+        return Err(SnapshotError::UnionMismatch);
+    }
+
+    Ok(())
+}
+
+impl<T: Pod, U: PodUnion + 'static> UnionVec<T, U> {
+    /// Writes a snapshot of the union-vector to `w`, that can be read back with
+    /// [`UnionVec::read_snapshot`].
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, f64)>::new();
+    /// union_vec.push(10);
+    /// union_vec.push(20);
+    ///
+    /// let mut snapshot = Vec::new();
+    /// union_vec.write_snapshot(&mut snapshot).unwrap();
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, f64)>::read_snapshot(&snapshot[..]).unwrap();
+    ///
+    /// assert_eq!(union_vec.pop(), Some(20));
+    /// ```
+    ///
+    /// # Panic
+    ///
+    /// When `T` is not a type of `U`, this function will panic.
+    pub fn write_snapshot<W: io::Write>(&self, mut w: W) -> io::Result<()> {
+        self.check_tag();
+
+        w.write_all(&MAGIC)?;
+        w.write_all(&[VERSION, ENDIAN])?;
+        write_fingerprint::<U, _>(&mut w)?;
+        write_u32(&mut w, selector_index::<T, U>() as u32)?;
+        write_u64(&mut w, self.len() as u64)?;
+
+        let size = mem::size_of::<T>();
+        let ptr = self.data.as_ptr() as *const u8;
+
+        // `T` is `Pod`, so it has no padding. Only when it fills the entire Union, the elements
+        // can be written at once.
+        if size == U::SIZE {
+            w.write_all(unsafe { slice::from_raw_parts(ptr, self.len() * size) })?;
+        } else {
+            for i in 0..self.len() {
+                w.write_all(unsafe { slice::from_raw_parts(ptr.add(i * U::SIZE), size) })?;
+            }
+        }
+
+        w.flush()
+    }
+
+    /// Reads a snapshot written by [`UnionVec::write_snapshot`]. The elements are read directly
+    /// into the stride of the Union.
+    ///
+    /// When the snapshot was written for a different Union or a different current type, an error
+    /// is returned. Only snapshots written by the same build of the program are guaranteed to be
+    /// accepted, because the fingerprint compares the names of the types.
+    pub fn read_snapshot<R: io::Read>(mut r: R) -> Result<Self, SnapshotError> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+
+        if magic != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+
+        match read_u8(&mut r)? {
+            VERSION => {}
+            version => return Err(SnapshotError::UnsupportedVersion(version)),
+        }
+
+        if read_u8(&mut r)? != ENDIAN {
+            return Err(SnapshotError::EndianMismatch);
+        }
+
+        check_fingerprint::<U, _>(&mut r)?;
+
+        let expected = selector_index::<T, U>();
+        let found = read_u32(&mut r)? as usize;
+
+        if found != expected {
+            return Err(SnapshotError::SelectorMismatch { expected, found });
+        }
+
+        let len = read_u64(&mut r)?;
+
+        if len > usize::MAX as u64 || (len as usize).checked_mul(U::SIZE).is_none() {
+            return Err(SnapshotError::TooLarge);
+        }
+
+        let len = len as usize;
+        let size = mem::size_of::<T>();
+
+        // Zero-sized elements are read all at once, as they take no memory.
+        let chunk = match U::SIZE {
+            0 => cmp::max(len, 1),
+            union_size => cmp::max(MAX_PREALLOCATE / union_size, 1),
+        };
+        let mut data: Vec<U::Union> = Vec::with_capacity(cmp::min(len, chunk));
+
+        // The elements are read in chunks, so a bogus length fails on the reader before much
+        // memory is allocated.
+        while data.len() < len {
+            let n = cmp::min(len - data.len(), chunk);
+            data.reserve_exact(n);
+
+            unsafe {
+                let ptr = data.as_mut_ptr().add(data.len()) as *mut u8;
+
+                // Zero the chunk first, so no uninitialized memory is handed to the reader.
+                ptr.write_bytes(0, n * U::SIZE);
+
+                if size == U::SIZE {
+                    r.read_exact(slice::from_raw_parts_mut(ptr, n * size))?;
+                } else {
+                    for i in 0..n {
+                        r.read_exact(slice::from_raw_parts_mut(ptr.add(i * U::SIZE), size))?;
+                    }
+                }
+
+                let new_len = data.len() + n;
+                data.set_len(new_len);
+            }
+        }
+
+        Ok(unsafe { UnionVec::from_data_unchecked(data) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot<T: Pod, U: PodUnion + 'static>(union_vec: &UnionVec<T, U>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        union_vec.write_snapshot(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip_padded() {
+        let mut union_vec = UnionVec::<u16, (u16, [u64; 2])>::new();
+
+        for n in 0..100 {
+            union_vec.push(n);
+        }
+
+        let buf = snapshot(&union_vec);
+        let mut read = UnionVec::<u16, (u16, [u64; 2])>::read_snapshot(&buf[..]).unwrap();
+
+        assert_eq!(read.len(), 100);
+
+        for n in (0..100).rev() {
+            assert_eq!(read.pop(), Some(n));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_union_mismatch() {
+        let mut union_vec = UnionVec::<u32, (u32, f32)>::new();
+        union_vec.push(1);
+
+        let buf = snapshot(&union_vec);
+
+        match UnionVec::<u32, (u32, f64)>::read_snapshot(&buf[..]) {
+            Err(SnapshotError::UnionMismatch) => {}
+            other => panic!("expected a union mismatch, got {:?}", other.map(|v| v.len())),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_selector_mismatch() {
+        let mut union_vec = UnionVec::<u32, (u32, f32)>::new();
+        union_vec.push(1);
+
+        let buf = snapshot(&union_vec);
+
+        match UnionVec::<f32, (u32, f32)>::read_snapshot(&buf[..]) {
+            Err(SnapshotError::SelectorMismatch {
+                expected: 1,
+                found: 0,
+            }) => {}
+            other => panic!("expected a selector mismatch, got {:?}", other.map(|v| v.len())),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_bad_magic() {
+        match UnionVec::<u32, (u32, f32)>::read_snapshot(&b"nope, not a snapshot"[..]) {
+            Err(SnapshotError::BadMagic) => {}
+            other => panic!("expected bad magic, got {:?}", other.map(|v| v.len())),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_huge_len_empty_body() {
+        let mut buf = snapshot(&UnionVec::<u32, (u32, f32)>::new());
+
+        // Overwrite the length, which is the last field of the header.
+        let at = buf.len() - 8;
+        buf[at..].copy_from_slice(&(1u64 << 60).to_le_bytes());
+
+        match UnionVec::<u32, (u32, f32)>::read_snapshot(&buf[..]) {
+            Err(SnapshotError::Io(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+            other => panic!("expected an unexpected eof, got {:?}", other.map(|v| v.len())),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Sorting, deduplication and searching on the current type of a union-vector.
+//!
+//! These operate directly on the internal Vector: elements are moved as whole unions, and are
+//! only ever compared as `T`.
+
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+
+use super::UnionVec;
+use select::TypeUnion;
+
+/// Views a union as the `T` it currently holds.
+#[inline]
+unsafe fn as_t<T, U: TypeUnion>(union: &U::Union) -> &T {
+    &*(union as *const U::Union as *const T)
+}
+
+/// Views a union as the `T` it currently holds.
+#[inline]
+unsafe fn as_t_mut<T, U: TypeUnion>(union: &mut U::Union) -> &mut T {
+    &mut *(union as *mut U::Union as *mut T)
+}
+
+/// Drops the `T`s held by the unions from `next` to `len`, also when dropping one of them panics.
+struct DropTail<T, U: TypeUnion> {
+    ptr: *mut U::Union,
+    next: usize,
+    len: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T, U: TypeUnion> Drop for DropTail<T, U> {
+    fn drop(&mut self) {
+        while self.next < self.len {
+            let i = self.next;
+            self.next += 1;
+
+            // Drops the rest of the tail when dropping the current element panics.
+            let rest = DropTail::<T, U> {
+                ptr: self.ptr,
+                next: self.next,
+                len: self.len,
+                marker: PhantomData,
+            };
+
+            unsafe { ptr::drop_in_place(self.ptr.add(i) as *mut T) };
+            mem::forget(rest);
+        }
+    }
+}
+
+impl<T, U: TypeUnion> UnionVec<T, U> {
+    /// Sorts the union-vector, see [`slice::sort`].
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, String)>::new();
+    ///
+    /// for n in [3, 1, 2] {
+    ///     union_vec.push(n);
+    /// }
+    ///
+    /// union_vec.sort();
+    ///
+    /// assert_eq!(union_vec.into_vec(), vec![1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp)
+    }
+
+    /// Sorts the union-vector with a comparator function, see [`slice::sort_by`].
+    #[inline]
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.check_tag();
+
+        unsafe {
+            self.data
+                .sort_by(|a, b| compare(as_t::<T, U>(a), as_t::<T, U>(b)))
+        }
+    }
+
+    /// Sorts the union-vector with a key extraction function, see [`slice::sort_by_key`].
+    #[inline]
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Sorts the union-vector with a comparator function, but might not preserve the order of
+    /// equal elements, see [`slice::sort_unstable_by`].
+    #[inline]
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.check_tag();
+
+        unsafe {
+            self.data
+                .sort_unstable_by(|a, b| compare(as_t::<T, U>(a), as_t::<T, U>(b)))
+        }
+    }
+
+    /// Removes consecutive repeated elements, see [`Vec::dedup`].
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let mut union_vec = UnionVec::<String, (String, u64)>::new();
+    ///
+    /// for s in ["b", "a", "b", "a"] {
+    ///     union_vec.push(String::from(s));
+    /// }
+    ///
+    /// union_vec.sort();
+    /// union_vec.dedup();
+    ///
+    /// assert_eq!(union_vec.into_vec(), vec!["a", "b"]);
+    /// ```
+    #[inline]
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    /// Removes all but the first of consecutive elements for which `same_bucket` returns `true`,
+    /// see [`Vec::dedup_by`].
+    ///
+    /// `same_bucket` is passed the element that is to be removed first.
+    #[inline]
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        self.check_tag();
+
+        let len = self.data.len();
+
+        if len <= 1 {
+            return;
+        }
+
+        let ptr = self.data.as_mut_ptr();
+        let mut write = 1;
+
+        unsafe {
+            // Every element stays a valid `T` while `same_bucket` runs: the kept elements are
+            // swapped to the front, the removed ones end up behind them.
+            for read in 1..len {
+                let a = as_t_mut::<T, U>(&mut *ptr.add(read));
+                let b = as_t_mut::<T, U>(&mut *ptr.add(write - 1));
+
+                if !same_bucket(a, b) {
+                    if read != write {
+                        ptr::swap(ptr.add(read), ptr.add(write));
+                    }
+
+                    write += 1;
+                }
+            }
+
+            // The removed elements are dropped after the compaction, so a panic while dropping
+            // one of them can not drop any element twice.
+            self.data.set_len(write);
+
+            drop(DropTail::<T, U> {
+                ptr,
+                next: write,
+                len,
+                marker: PhantomData,
+            });
+        }
+    }
+
+    /// Binary searches the sorted union-vector with a comparator function, see
+    /// [`slice::binary_search_by`].
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::collections::unionvec::UnionVec;
+    ///
+    /// let mut union_vec = UnionVec::<u32, (u32, String)>::new();
+    ///
+    /// for n in [1, 3, 5] {
+    ///     union_vec.push(n);
+    /// }
+    ///
+    /// assert_eq!(union_vec.binary_search_by(|n| n.cmp(&3)), Ok(1));
+    /// assert_eq!(union_vec.binary_search_by(|n| n.cmp(&4)), Err(2));
+    /// ```
+    #[inline]
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        self.check_tag();
+
+        unsafe { self.data.binary_search_by(|union| f(as_t::<T, U>(union))) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use index::Type2;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_sort_moves_whole_unions() {
+        let mut union_vec = UnionVec::<u8, (u8, u64)>::new();
+
+        for n in [5, 255, 0, 7] {
+            union_vec.push(n);
+        }
+
+        union_vec.sort_by_key(|n| u8::MAX - n);
+        assert_eq!(union_vec.binary_search_by(|n| 7.cmp(n)), Ok(1));
+
+        let union_vec = union_vec.map::<Type2, _>(u64::from);
+        assert_eq!(union_vec.into_vec(), vec![255, 7, 5, 0]);
+    }
+
+    #[test]
+    fn test_dedup_by_drops_removed() {
+        let rc = Rc::new(());
+
+        let mut union_vec = UnionVec::<(u32, Rc<()>), ((u32, Rc<()>), u64)>::new();
+
+        for n in [1, 1, 2, 2, 2, 3] {
+            union_vec.push((n, Rc::clone(&rc)));
+        }
+
+        union_vec.dedup_by(|a, b| a.0 == b.0);
+
+        assert_eq!(union_vec.len(), 3);
+        assert_eq!(Rc::strong_count(&rc), 4);
+    }
+
+    #[test]
+    fn test_dedup_by_panicking_drop() {
+        use std::cell::Cell;
+        use std::panic::{self, AssertUnwindSafe};
+
+        struct Counted<'a>(u32, bool, &'a Cell<usize>);
+
+        impl<'a> Drop for Counted<'a> {
+            fn drop(&mut self) {
+                self.2.set(self.2.get() + 1);
+
+                if self.1 {
+                    panic!("drop of {}", self.0);
+                }
+            }
+        }
+
+        let drops = Cell::new(0);
+
+        let mut union_vec = UnionVec::<Counted, (Counted, u64)>::new();
+
+        for &(n, panics) in &[(0, false), (0, false), (1, false), (1, true), (2, false)] {
+            union_vec.push(Counted(n, panics, &drops));
+        }
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            union_vec.dedup_by(|a, b| a.0 == b.0)
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(union_vec.len(), 3);
+        assert_eq!(drops.get(), 2);
+
+        drop(union_vec);
+        assert_eq!(drops.get(), 5);
+    }
+}
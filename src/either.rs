@@ -0,0 +1,30 @@
+//! A value of one of two types.
+
+/// A value that is either a `Left` or a `Right`.
+///
+/// This is used to route elements to one of two outputs, see `UnionVec::partition_map`.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Either<L, R> {
+    /// A value of the left type.
+    Left(L),
+
+    /// A value of the right type.
+    Right(R),
+}
+
+impl<L, R> Either<L, R> {
+    /// Returns `true` if the value is a `Left`.
+    #[inline]
+    pub fn is_left(&self) -> bool {
+        match *self {
+            Either::Left(_) => true,
+            Either::Right(_) => false,
+        }
+    }
+
+    /// Returns `true` if the value is a `Right`.
+    #[inline]
+    pub fn is_right(&self) -> bool {
+        !self.is_left()
+    }
+}
@@ -1,21 +1,51 @@
-use select::{Select, Selector};
+use select::{Contains, Select, Selector, SubsetOf};
 
-macro_rules! IndexStruct {
-    ($name: ident $(, $names:ident)*) => (
-        /// This type is used to 'index' into a tuple of generics.
-        /// See [`Select`] what Generic it selects.
-        #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
-        pub struct $name;
+/// This type is used to 'index' into a tuple of generics, with a zero-based index `N`.
+/// See [`Select`] what Generic it selects.
+///
+/// Because the index is a const generic, selectors can be computed in generic code.
+///
+/// # Examples
+/// ```
+/// extern crate unioncollections;
+///
+/// use unioncollections::collections::unionvec::UnionVec;
+/// use unioncollections::index::{Idx, Type2};
+///
+/// let mut union_vec = UnionVec::<u8, (u8, u16)>::new();
+/// union_vec.push(1);
+///
+/// let union_vec = union_vec.map::<Idx<1>, _>(|n| n as u16 * 1000);
+/// let mut union_vec = union_vec.map::<Type2, _>(|n| n + 1);
+///
+/// assert_eq!(union_vec.pop(), Some(1001));
+/// ```
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct Idx<const N: usize>;
 
-        impl Selector for $name {}
-
-        IndexStruct!($($names),*);
-    );
+impl<const N: usize> Selector for Idx<N> {
+    const INDEX: usize = N;
+}
 
-    () => ();
+macro_rules! IndexAlias {
+    ($($name:ident = $index:expr),*) => ($(
+        #[doc = concat!("Selects the Generic at index ", stringify!($index), ", see [`Idx`].")]
+        pub type $name = Idx<$index>;
+    )*);
 }
 
-IndexStruct!(Type1, Type2, Type3, Type4, Type5, Type6, Type7, Type8, Type9, Type10);
+IndexAlias!(
+    Type1 = 0,
+    Type2 = 1,
+    Type3 = 2,
+    Type4 = 3,
+    Type5 = 4,
+    Type6 = 5,
+    Type7 = 6,
+    Type8 = 7,
+    Type9 = 8,
+    Type10 = 9
+);
 
 macro_rules! impl_select {
     (
@@ -23,13 +53,12 @@ macro_rules! impl_select {
         GENERICS = [$current:tt $(,$generics:tt)*],
         COPIES = [$($copies:tt),*]
     ) => (
-        impl <$($copies),*> Select<$name> for ($($copies),*)
-        where
-            $current: 'static
-        {
+        impl <$($copies),*> Select<$name> for ($($copies),*) {
             type Output = $current;
         }
 
+        impl <$($copies),*> Contains<$current, $name> for ($($copies),*) {}
+
         impl_select!(
             NAMES = [$($names),*],
             GENERICS = [$($generics),*],
@@ -110,3 +139,25 @@ select!(
     NAMES = [Type1, Type2, Type3, Type4, Type5, Type6, Type7, Type8, Type9, Type10],
     GENERICS = [A, B, C, D, E, F, G, H, I, J]
 );
+
+macro_rules! subset {
+    ($($generics:ident: $selectors:ident),*) => (
+        impl <U, $($generics, $selectors),*> SubsetOf<U, ($($selectors),*)> for ($($generics),*)
+        where
+            $(
+                $selectors: Selector,
+                U: Contains<$generics, $selectors>,
+            )*
+        {}
+    );
+}
+
+subset!(A: SA, B: SB);
+subset!(A: SA, B: SB, C: SC);
+subset!(A: SA, B: SB, C: SC, D: SD);
+subset!(A: SA, B: SB, C: SC, D: SD, E: SE);
+subset!(A: SA, B: SB, C: SC, D: SD, E: SE, F: SF);
+subset!(A: SA, B: SB, C: SC, D: SD, E: SE, F: SF, G: SG);
+subset!(A: SA, B: SB, C: SC, D: SD, E: SE, F: SF, G: SG, H: SH);
+subset!(A: SA, B: SB, C: SC, D: SD, E: SE, F: SF, G: SG, H: SH, I: SI);
+subset!(A: SA, B: SB, C: SC, D: SD, E: SE, F: SF, G: SG, H: SH, I: SI, J: SJ);
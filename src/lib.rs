@@ -1,22 +1,25 @@
-#![feature(const_fn)]
-#![feature(const_type_id)]
-#![feature(untagged_unions)]
-#![feature(allocator_api)]
-
 //#![feature(trace_macros)]
 //trace_macros!(true);
 
 extern crate core;
+extern crate unioncollections_derive;
+
+#[cfg(feature = "serde")]
+extern crate serde;
 //pub mod selectvec;
 
 //mod macros;
 //pub use macros::*;
 
 // Modularized.
+pub mod aligned;
+pub mod either;
 pub mod index;
+pub mod pod;
 pub mod select;
 pub mod union;
 
 pub mod collections;
 
 pub use union::type_id;
+pub use unioncollections_derive::type_union;
@@ -0,0 +1,46 @@
+//! Plain-old-data types, that can be reinterpreted as one another.
+
+use select::TypeUnion;
+
+/// Marker trait for plain-old-data types.
+///
+/// A type implementing `Pod` is `Copy`, has no padding bytes, and every bit pattern is a valid
+/// value of it. This allows a buffer of one `Pod` type to be reinterpreted as a buffer of an
+/// other `Pod` type of the same size, without touching the elements.
+///
+/// # Safety
+///
+/// Implementing this trait for a type that does not uphold the above is undefined behaviour.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => (
+        $(
+            unsafe impl Pod for $ty {}
+        )*
+    );
+}
+
+macro_rules! impl_pod_array {
+    ($($n:expr),*) => (
+        $(
+            unsafe impl<T: Pod> Pod for [T; $n] {}
+        )*
+    );
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl_pod_array!(
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32, 64, 128, 256, 512, 1024, 2048, 4096
+);
+
+/// Marker trait for Unions of which every type is [`Pod`].
+///
+/// The tuple Unions implement this when all of their types are `Pod`.
+///
+/// # Safety
+///
+/// Every type of the Union must implement [`Pod`].
+pub unsafe trait PodUnion: TypeUnion {}
@@ -1,3 +1,5 @@
+use std::alloc::Layout;
+use std::any::TypeId;
 use std::fmt;
 use std::mem;
 use std::ptr;
@@ -6,46 +8,243 @@ use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
 /// Helper trait to index into a tuple of Generics.
-pub trait Selector {}
+///
+/// `INDEX` has no default, every selector gives the index of the type it selects.
+pub trait Selector {
+    /// The index of the type it selects, starting at 0.
+    const INDEX: usize;
+}
 
 /// Helper trait to 'select' a generic type out of a tuple of Generics.
 pub trait Select<S: Selector> {
     /// The selected generic.
-    type Output: 'static;
+    type Output;
 }
 
+/// The type of the Union `U` that is selected by `S`.
+pub type Selected<U, S> = <U as Select<S>>::Output;
+
+/// Helper trait to check at compiletime whether `T` is one of the types of a tuple of Generics.
+///
+/// This is the counterpart of [`Select`]: `S` selects `T` out of `Self`. Because `T` is a
+/// parameter of the trait rather than an associated type, `S` can be infered from `T`.
+pub trait Contains<T, S: Selector> {}
+
+/// Helper trait to check at compiletime whether every type of a tuple of Generics is also a type
+/// of `U`.
+///
+/// `I` is a tuple of the [`Selector`]s that select each type of `Self` out of `U`. It is always
+/// infered, which means every type of `Self` has to occur exactly once in `U`.
+pub trait SubsetOf<U, I> {}
+
 /// This trait offers functions to cast any type, into any other type, constraintly.
+///
+/// # Safety
+///
+/// The implementing type must be a Union of the types of `U`, that holds each of them at its
+/// start.
 pub unsafe trait TypeSelect<U: TypeUnion>: Sized {
     /// Casts `self` to `T`.
     ///
-    /// # Panic
+    /// Only the layout of `T` is checked, in debug builds. Use [`TypeSelect::select`] to have
+    /// `T` checked at compiletime, or [`TypeSelect::cast_checked`] to have it checked on
+    /// `TypeId`'s in debug builds.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be one of the types of `U`, and `self` must hold a value of it.
+    #[inline]
+    unsafe fn cast<T>(self) -> T {
+        debug_assert!(U::member_layouts().contains(&Layout::new::<T>()));
+        let this = mem::ManuallyDrop::new(self);
+        ptr::read(&*this as *const Self as *const T)
+    }
+
+    /// Casts `self` to `T`, like [`TypeSelect::cast`].
+    ///
+    /// In debug builds, `T` is checked to be one of the types of `U` with [`TypeUnion::contains`],
+    /// which is why `T` and every type of `U` must be `'static`.
     ///
-    /// When `self` can not be safely cast to `T`, this function will panic.
+    /// # Safety
+    ///
+    /// `self` must hold a value of `T`.
     #[inline]
-    unsafe fn cast<T: 'static>(self) -> T {
+    unsafe fn cast_checked<T: 'static>(self) -> T
+    where
+        U: 'static,
+    {
         debug_assert!(U::contains::<T>());
-        let mut s = mem::uninitialized();
-        ptr::write(&mut s as *mut _ as *mut Self, self);
-        s
+        self.cast()
     }
 
+    /// Wraps `self` in a `SelectHandle` of the type selected by `S`.
+    ///
+    /// # Safety
+    ///
+    /// `self` must hold a value of the type selected by `S`.
     #[inline]
     unsafe fn select<S>(self) -> SelectHandle<<U as Select<S>>::Output, U>
     where
         S: Selector,
         U: Select<S>,
     {
-        self.cast()
+        let this = mem::ManuallyDrop::new(self);
+        SelectHandle::from_inner(ptr::read(&*this as *const Self as *const U::Union))
     }
 }
 
-pub trait TypeUnion: Sized + 'static {
+/// A marker for Unions with a C-compatible layout: `#[repr(C)]`, with every type at offset 0.
+///
+/// Unions declared with `type_union!` implement this when they are declared with `#[repr(C)]`.
+/// The tuple Unions only implement it with the `repr-c` feature.
+///
+/// # Safety
+///
+/// The Union must be `#[repr(C)]`.
+pub unsafe trait ReprC: TypeUnion {}
+
+/// Describes a type of a Union, see [`TypeUnion::for_each_member`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MemberInfo {
+    /// The index of the type, which is the [`Selector::INDEX`] of its selector.
+    pub index: usize,
+
+    /// The TypeId of the type.
+    pub type_id: TypeId,
+
+    /// The layout of the type.
+    pub layout: Layout,
+
+    /// The name of the type.
+    pub name: &'static str,
+}
+
+/// A visitor over the types of the Union `U`, see [`TypeUnion::for_each_member`].
+pub trait MemberVisitor<U: TypeUnion> {
+    /// Visits the type of `U` selected by `S`.
+    fn visit<S>(&mut self, info: MemberInfo)
+    where
+        S: Selector,
+        U: Select<S>;
+}
+
+pub trait TypeUnion: Sized {
     type Union: TypeSelect<Self>;
 
-    /// This function should only be used with tuples.
+    /// The size of the Union, in bytes.
+    const SIZE: usize = mem::size_of::<Self::Union>();
+
+    /// The alignment of the Union, in bytes.
+    const ALIGN: usize = mem::align_of::<Self::Union>();
+
+    /// The layouts of the types of the Union, in the order they are selected in.
+    const MEMBER_LAYOUTS: &'static [Layout];
+
+    /// Returns `true` if T is one of the types of the Union, false otherwise.
+    ///
+    /// This is a runtime check on `TypeId`'s, and is therefore only available when all types of
+    /// the Union are `'static`. Membership is otherwise proven at compiletime, by [`Select`] and
+    /// [`Contains`].
+    fn contains<T: 'static>() -> bool
+    where
+        Self: 'static;
+
+    /// Returns the layouts of the types of the Union, in the order they are selected in.
+    #[inline]
+    fn member_layouts() -> &'static [Layout] {
+        Self::MEMBER_LAYOUTS
+    }
+
+    /// Returns the TypeId's of the types of the Union, in the order they are selected in.
+    fn member_type_ids() -> Vec<TypeId>
+    where
+        Self: 'static;
+
+    /// Returns the index of `T` in the Union, or `None` if `T` is not one of its types.
+    /// When `T` occurs more than once, the first index is returned.
+    #[inline]
+    fn index_of<T: 'static>() -> Option<usize>
+    where
+        Self: 'static,
+    {
+        Self::member_type_ids()
+            .iter()
+            .position(|id| *id == TypeId::of::<T>())
+    }
+
+    /// Returns the names of the types of the Union, in the order they are selected in.
+    fn member_type_names() -> Vec<&'static str>;
+
+    /// Drops the value of the `index`th type of the Union, in place.
+    ///
+    /// # Panic
+    ///
+    /// When `index` is not less than the number of types of the Union, this function will panic.
+    ///
+    /// # Safety
+    ///
+    /// `union` must hold a value of the `index`th type.
+    unsafe fn drop_member(index: usize, union: *mut Self::Union);
+
+    /// Calls the visitor once for every type of the Union, in the order they are selected in.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::select::{MemberInfo, MemberVisitor, Select, Selector, TypeUnion};
     ///
-    /// Returns `true` if T is one of the types of the tuple, false otherwise.
-    fn contains<T: 'static>() -> bool;
+    /// struct Names(Vec<(usize, &'static str)>);
+    ///
+    /// impl<U: TypeUnion> MemberVisitor<U> for Names {
+    ///     fn visit<S>(&mut self, info: MemberInfo)
+    ///     where
+    ///         S: Selector,
+    ///         U: Select<S>,
+    ///     {
+    ///         self.0.push((S::INDEX, info.name));
+    ///     }
+    /// }
+    ///
+    /// let mut names = Names(Vec::new());
+    /// <(u8, String)>::for_each_member(&mut names);
+    ///
+    /// assert_eq!(names.0, vec![(0, "u8"), (1, "alloc::string::String")]);
+    /// ```
+    fn for_each_member<V: MemberVisitor<Self>>(visitor: &mut V)
+    where
+        Self: 'static;
+
+    /// Returns the number of bytes that are wasted per element, when the Union holds a `T`.
+    #[inline]
+    fn waste_for<T>() -> usize {
+        Self::SIZE.saturating_sub(mem::size_of::<T>())
+    }
+}
+
+/// A Union that has an enum counterpart, with a variant for every type of the Union.
+///
+/// This is implemented for the tuple Unions, with the `OneOf` enums of `unioncollections::union`.
+pub trait EnumUnion: TypeUnion {
+    /// The enum counterpart of the Union.
+    type Enum;
+
+    /// Returns the index of the type held by the enum.
+    fn enum_index(e: &Self::Enum) -> usize;
+
+    /// Moves the value held by the enum into a Union.
+    fn enum_into_union(e: Self::Enum) -> Self::Union;
+
+    /// Moves the value held by the Union into the enum.
+    ///
+    /// # Panic
+    ///
+    /// When `index` is not less than the number of types of the Union, this function will panic.
+    ///
+    /// # Safety
+    ///
+    /// `union` must hold a value of the `index`th type.
+    unsafe fn union_into_enum(index: usize, union: Self::Union) -> Self::Enum;
 }
 
 /// A wrapper around Unions, that keeps track of the current type using PhantomData.
@@ -59,11 +258,15 @@ pub struct SelectHandle<T, U: TypeUnion> {
 
 impl<T, U: TypeUnion> SelectHandle<T, U> {
     /// Creates a new Union, and writes the given value to it.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be one of the types of `U`.
     #[inline]
     pub unsafe fn from_unchecked(t: T) -> Self {
-        let mut s = mem::uninitialized();
-        ptr::write(&mut s as *mut _ as *mut T, t);
-        s
+        let mut s = mem::MaybeUninit::<Self>::uninit();
+        ptr::write(s.as_mut_ptr() as *mut T, t);
+        s.assume_init()
     }
 
     /// Converts `self` into `T`.
@@ -77,6 +280,10 @@ impl<T, U: TypeUnion> SelectHandle<T, U> {
     }
 
     /// Creates a new `SelectHandle` from a Union.
+    ///
+    /// # Safety
+    ///
+    /// `data` must hold a value of `T`.
     #[inline]
     pub unsafe fn from_inner(data: U::Union) -> Self {
         Self {
@@ -87,13 +294,79 @@ impl<T, U: TypeUnion> SelectHandle<T, U> {
 
     /// Returns the underlying Union.
     #[inline]
-    pub fn into_inner(mut self) -> U::Union {
-        let data = unsafe { ptr::read(&mut self.data) };
+    pub fn into_inner(self) -> U::Union {
+        let data = unsafe { ptr::read(&self.data) };
         mem::forget(self);
         data
     }
 }
 
+impl<T, U: EnumUnion> SelectHandle<T, U> {
+    /// Moves the value held by the enum into a `SelectHandle`, if it holds the type selected by
+    /// `S`. Otherwise the enum is returned as is.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::index::{Type1, Type2};
+    /// use unioncollections::select::SelectHandle;
+    /// use unioncollections::union::OneOf2;
+    ///
+    /// let e = OneOf2::<u8, String>::Type2(String::from("two"));
+    ///
+    /// let e = SelectHandle::<u8, (u8, String)>::try_from_enum::<Type1>(e).unwrap_err();
+    /// let handle = SelectHandle::<String, (u8, String)>::try_from_enum::<Type2>(e).unwrap();
+    ///
+    /// assert_eq!(&*handle, "two");
+    /// ```
+    #[inline]
+    pub fn try_from_enum<S>(e: U::Enum) -> Result<Self, U::Enum>
+    where
+        S: Selector,
+        U: Select<S, Output = T>,
+    {
+        if U::enum_index(&e) == S::INDEX {
+            Ok(unsafe { Self::from_inner(U::enum_into_union(e)) })
+        } else {
+            Err(e)
+        }
+    }
+
+    /// Moves the value held by the `SelectHandle` into the variant of `T` of the enum.
+    ///
+    /// The variant is selected by `S` at compiletime. When `T` occurs more than once in `U`, `S`
+    /// can not be infered and has to be given. Converting the `SelectHandle` into the enum with
+    /// `From` looks up the variant at runtime instead, and moves the value into the first variant
+    /// of `T`.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate unioncollections;
+    ///
+    /// use unioncollections::index::Type3;
+    /// use unioncollections::select::SelectHandle;
+    /// use unioncollections::union::OneOf3;
+    ///
+    /// let handle = SelectHandle::<u32, (u8, String, u32)>::from(3);
+    /// assert_eq!(handle.into_enum(), OneOf3::Type3(3));
+    ///
+    /// let handle = SelectHandle::<u32, (u32, String, u32)>::from(3);
+    /// assert_eq!(handle.into_enum::<Type3>(), OneOf3::Type3(3));
+    ///
+    /// let handle = SelectHandle::<u32, (u32, String, u32)>::from(3);
+    /// assert_eq!(OneOf3::from(handle), OneOf3::Type1(3));
+    /// ```
+    #[inline]
+    pub fn into_enum<S>(self) -> U::Enum
+    where
+        S: Selector,
+        U: Contains<T, S>,
+    {
+        unsafe { U::union_into_enum(S::INDEX, self.into_inner()) }
+    }
+}
+
 impl<T, U: TypeUnion> From<T> for SelectHandle<T, U> {
     #[inline]
     fn from(t: T) -> Self {
@@ -124,6 +397,17 @@ impl<T: fmt::Debug, U: TypeUnion> fmt::Debug for SelectHandle<T, U> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<T, U: TypeUnion> ::serde::Serialize for SelectHandle<T, U>
+where
+    T: ::serde::Serialize,
+{
+    #[inline]
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.deref().serialize(serializer)
+    }
+}
+
 // @TODO: Fix this.
 impl<T, U: TypeUnion> Clone for SelectHandle<T, U>
 where
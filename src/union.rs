@@ -1,6 +1,15 @@
-use std::any::TypeId;
+use std::alloc::Layout;
+use std::any::{type_name, TypeId};
+use std::mem::ManuallyDrop;
+use std::ptr;
 
-use select::{TypeSelect, TypeUnion};
+use index::{Type1, Type10, Type2, Type3, Type4, Type5, Type6, Type7, Type8, Type9};
+use pod::{Pod, PodUnion};
+#[cfg(feature = "repr-c")]
+use select::ReprC;
+use select::{
+    EnumUnion, MemberInfo, MemberVisitor, SelectHandle, Selector, TypeSelect, TypeUnion,
+};
 
 macro_rules! doc_comment {
     ($x:expr, $($tt:tt)*) => {
@@ -21,6 +30,26 @@ macro_rules! contains_type {
     )
 }
 
+/// Calls `$visitor` for every generic, with the selector at the same position.
+macro_rules! visit_members {
+    (
+        $visitor:ident,
+        [$generic:tt $(,$generics:tt)*],
+        [$selector:ident $(,$selectors:ident)*]
+    ) => (
+        $visitor.visit::<$selector>(MemberInfo {
+            index: <$selector as Selector>::INDEX,
+            type_id: type_id::<$generic>(),
+            layout: Layout::new::<$generic>(),
+            name: type_name::<$generic>(),
+        });
+
+        visit_members!($visitor, [$($generics),*], [$($selectors),*]);
+    );
+
+    ($visitor:ident, [], [$($selectors:ident),*]) => ();
+}
+
 macro_rules! Union {
     (   $d:tt,
         pub union $name:ident {
@@ -29,26 +58,67 @@ macro_rules! Union {
         doc_comment!(
             concat!("This union can hold the following Generics: ", stringify!($($generics),*)),
             #[derive(Copy, Clone)]
+            #[allow(dead_code)]
+            #[cfg_attr(feature = "repr-c", repr(C))]
             pub union $name<$($generics),*> {
-                $($fieldnames: $generics,)*
+                $($fieldnames: ManuallyDrop<$generics>,)*
             }
 
-            impl <$($generics),*> TypeUnion for ($($generics),*)
-            where
-                $($generics: 'static),*
-            {
+            impl <$($generics),*> TypeUnion for ($($generics),*) {
                 type Union = $name<$($generics),*>;
 
+                const MEMBER_LAYOUTS: &'static [Layout] = &[$(Layout::new::<$generics>()),*];
+
                 #[inline]
-                fn contains<T: 'static>() -> bool {
+                fn contains<T: 'static>() -> bool
+                where
+                    Self: 'static,
+                {
                     contains_type!(T, [$($generics),*])
                 }
+
+                #[inline]
+                fn member_type_ids() -> Vec<TypeId>
+                where
+                    Self: 'static,
+                {
+                    vec![$(type_id::<$generics>()),*]
+                }
+
+                #[inline]
+                fn member_type_names() -> Vec<&'static str> {
+                    vec![$(type_name::<$generics>()),*]
+                }
+
+                #[inline]
+                unsafe fn drop_member(index: usize, union: *mut Self::Union) {
+                    unsafe fn drop_as<T>(ptr: *mut u8) {
+                        ptr::drop_in_place(ptr as *mut T)
+                    }
+
+                    let drops: &[unsafe fn(*mut u8)] = &[$(drop_as::<$generics>),*];
+                    drops[index](union as *mut u8)
+                }
+
+                #[inline]
+                fn for_each_member<V: MemberVisitor<Self>>(visitor: &mut V)
+                where
+                    Self: 'static,
+                {
+                    visit_members!(
+                        visitor,
+                        [$($generics),*],
+                        [Type1, Type2, Type3, Type4, Type5, Type6, Type7, Type8, Type9, Type10]
+                    );
+                }
             }
 
-            unsafe impl <$($generics),*> TypeSelect<($($generics),*)> for $name<$($generics),*>
-            where
-                $($generics: 'static),*
-            {}
+            unsafe impl <$($generics),*> TypeSelect<($($generics),*)> for $name<$($generics),*> {}
+
+            unsafe impl <$($generics: Pod),*> PodUnion for ($($generics),*) {}
+
+            #[cfg(feature = "repr-c")]
+            unsafe impl <$($generics),*> ReprC for ($($generics),*) {}
         );
     )
 }
@@ -81,3 +151,90 @@ GenUnion!(
     NAMES = [Union10, Union9, Union8, Union7, Union6, Union5, Union4, Union3, Union2],
     FIELDS = [a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J]
 );
+
+macro_rules! OneOf {
+    ($name:ident { $($variants:ident: $generics:ident),* }) => (
+        doc_comment!(
+            concat!(
+                "A value of one of the following Generics: ",
+                stringify!($($generics),*),
+                ".\n\nEvery variant is named after the selector of its Generic."
+            ),
+            #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+            pub enum $name<$($generics),*> {
+                $(
+                    #[doc = concat!("A value of the Generic selected by [`", stringify!($variants), "`].")]
+                    $variants($generics),
+                )*
+            }
+        );
+
+        impl <$($generics),*> EnumUnion for ($($generics),*) {
+            type Enum = $name<$($generics),*>;
+
+            #[inline]
+            fn enum_index(e: &Self::Enum) -> usize {
+                match *e {
+                    $($name::$variants(_) => <$variants as Selector>::INDEX,)*
+                }
+            }
+
+            #[inline]
+            fn enum_into_union(e: Self::Enum) -> Self::Union {
+                match e {
+                    $($name::$variants(x) => SelectHandle::<$generics, Self>::from(x).into_inner(),)*
+                }
+            }
+
+            #[inline]
+            unsafe fn union_into_enum(index: usize, union: Self::Union) -> Self::Enum {
+                let ptr = &union as *const Self::Union;
+
+                $(
+                    if index == <$variants as Selector>::INDEX {
+                        return $name::$variants(ptr::read(ptr as *const $generics));
+                    }
+                )*
+
+                panic!("index out of range for {}", stringify!($name))
+            }
+        }
+
+        /// Moves the value held by the `SelectHandle` into the first variant of `T`, see
+        /// [`SelectHandle::into_enum`] to select the variant at compiletime instead.
+        ///
+        /// # Panic
+        ///
+        /// When `T` is not one of the Generics of the enum, this function will panic.
+        impl <T, $($generics),*> From<SelectHandle<T, ($($generics),*)>> for $name<$($generics),*>
+        where
+            T: 'static,
+            $($generics: 'static),*
+        {
+            #[inline]
+            fn from(handle: SelectHandle<T, ($($generics),*)>) -> Self {
+                let index = <($($generics),*)>::index_of::<T>()
+                    .expect("`T` is not a type of the union");
+
+                unsafe { <($($generics),*)>::union_into_enum(index, handle.into_inner()) }
+            }
+        }
+    );
+}
+
+OneOf!(OneOf2 { Type1: A, Type2: B });
+OneOf!(OneOf3 { Type1: A, Type2: B, Type3: C });
+OneOf!(OneOf4 { Type1: A, Type2: B, Type3: C, Type4: D });
+OneOf!(OneOf5 { Type1: A, Type2: B, Type3: C, Type4: D, Type5: E });
+OneOf!(OneOf6 { Type1: A, Type2: B, Type3: C, Type4: D, Type5: E, Type6: F });
+OneOf!(OneOf7 { Type1: A, Type2: B, Type3: C, Type4: D, Type5: E, Type6: F, Type7: G });
+OneOf!(OneOf8 {
+    Type1: A, Type2: B, Type3: C, Type4: D, Type5: E, Type6: F, Type7: G, Type8: H
+});
+OneOf!(OneOf9 {
+    Type1: A, Type2: B, Type3: C, Type4: D, Type5: E, Type6: F, Type7: G, Type8: H, Type9: I
+});
+OneOf!(OneOf10 {
+    Type1: A, Type2: B, Type3: C, Type4: D, Type5: E, Type6: F, Type7: G, Type8: H, Type9: I,
+    Type10: J
+});
@@ -0,0 +1,189 @@
+extern crate unioncollections;
+
+use unioncollections::collections::dynunionvec::DynUnionVec;
+use unioncollections::collections::unionvec::UnionVec;
+use unioncollections::select::{
+    MemberInfo, MemberVisitor, Select, SelectHandle, Selector, TypeUnion,
+};
+use unioncollections::type_union;
+
+type_union! {
+    /// The stages a line of input goes through.
+    pub union Stage {
+        Raw: String,
+        Parsed: u64,
+        Bytes: Vec<u8>
+    }
+}
+
+type_union! {
+    /// The tokens of a zero-copy parser.
+    union Token<'a> {
+        Text: &'a str,
+        Number: u64
+    }
+}
+
+type_union! {
+    #[repr(C)]
+    union Pixel {
+        Rgba: [u8; 4],
+        Float: [f32; 4]
+    }
+}
+
+type_union! {
+    #[repr(align(16), C)]
+    union AlignedPixel {
+        AlignedRgba: [u8; 4],
+        AlignedFloat: [f32; 4]
+    }
+}
+
+type_union! {
+    union Wide {
+        M1: u8,
+        M2: u16,
+        M3: u32,
+        M4: u64,
+        M5: i8,
+        M6: i16,
+        M7: i32,
+        M8: i64,
+        M9: f32,
+        M10: f64,
+        M11: String
+    }
+}
+
+#[test]
+fn named_selectors() {
+    let mut v = UnionVec::<String, Stage>::new();
+
+    for s in ["10", "20", "30"] {
+        v.push(String::from(s));
+    }
+
+    let v = v.map::<Parsed, _>(|s| s.parse().unwrap());
+    let mut v = v.map::<Bytes, _>(|n| vec![n as u8]);
+
+    assert_eq!(v.len(), 3);
+    assert_eq!(v.pop(), Some(vec![30]));
+}
+
+#[test]
+fn contains_members() {
+    assert!(Stage::contains::<String>());
+    assert!(Stage::contains::<Vec<u8>>());
+    assert!(!Stage::contains::<u32>());
+}
+
+#[test]
+fn member_layouts() {
+    assert_eq!(Stage::member_layouts().len(), 3);
+    assert_eq!(Stage::member_type_names()[1], "u64");
+    assert_eq!(<Stage as TypeUnion>::SIZE, ::std::mem::size_of::<String>());
+}
+
+#[test]
+fn more_than_ten_members() {
+    assert!(Wide::contains::<String>());
+
+    let handle = SelectHandle::<String, Wide>::from(String::from("eleven"));
+    assert_eq!(&*handle, "eleven");
+
+    let v = UnionVec::<u8, Wide>::new();
+    let mut v = v.change_to::<M11>();
+    v.push(String::from("eleven"));
+
+    assert_eq!(v.pop(), Some(String::from("eleven")));
+}
+
+#[test]
+fn widen_into_named_union() {
+    let mut v = UnionVec::<u64, (u64, String)>::new();
+    v.push(10);
+
+    let v = v.widen::<Stage, _>();
+    let mut v = v.map::<Raw, _>(|n| n.to_string());
+
+    assert_eq!(v.pop(), Some(String::from("10")));
+}
+
+#[test]
+fn dyn_union_vec_with_named_selectors() {
+    let mut v = UnionVec::<String, Stage>::new();
+    v.push(String::from("raw"));
+
+    let mut v = DynUnionVec::from(v);
+    assert_eq!(v.index(), 0);
+
+    v.change_to_index(2).unwrap();
+
+    let mut v = v.downcast::<Bytes>().unwrap();
+    v.push(vec![1, 2, 3]);
+
+    assert_eq!(v.pop(), Some(vec![1, 2, 3]));
+}
+
+struct Members(Vec<MemberInfo>);
+
+impl<U: TypeUnion> MemberVisitor<U> for Members {
+    fn visit<S>(&mut self, info: MemberInfo)
+    where
+        S: Selector,
+        U: Select<S>,
+    {
+        assert_eq!(S::INDEX, info.index);
+        self.0.push(info);
+    }
+}
+
+#[test]
+fn visit_every_member() {
+    let mut members = Members(Vec::new());
+    Wide::for_each_member(&mut members);
+
+    assert_eq!(members.0.len(), 11);
+    assert_eq!(members.0[10].name, Wide::member_type_names()[10]);
+
+    let layouts: Vec<_> = members.0.iter().map(|info| info.layout).collect();
+    assert_eq!(&layouts[..], Wide::member_layouts());
+}
+
+#[test]
+fn borrowed_members() {
+    let input = String::from("1 2 3");
+
+    let mut v = UnionVec::<&str, Token>::new();
+
+    for s in input.split(' ') {
+        v.push(s);
+    }
+
+    let v = v.map::<Number, _>(|s| s.parse().unwrap());
+
+    assert_eq!(v.into_vec(), vec![1, 2, 3]);
+}
+
+#[test]
+fn repr_c_union() {
+    let mut v = UnionVec::<[u8; 4], Pixel>::new();
+    v.push([1, 2, 3, 4]);
+
+    let view = v.as_ffi_slice();
+
+    assert_eq!(view.len, 1);
+    assert_eq!(view.stride, ::std::mem::size_of::<[f32; 4]>());
+}
+
+#[test]
+fn repr_c_union_with_other_hints() {
+    let mut v = UnionVec::<[f32; 4], AlignedPixel>::new();
+    v.push([1.0, 2.0, 3.0, 4.0]);
+
+    let view = v.as_ffi_slice();
+
+    assert_eq!(view.len, 1);
+    assert_eq!(view.stride, 16);
+}
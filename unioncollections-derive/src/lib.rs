@@ -0,0 +1,235 @@
+//! Procedural macros for `unioncollections`.
+//!
+//! The unions in `unioncollections::union` are anonymous: their members are the generics of a
+//! tuple, and they are selected with `Type1`, `Type2`, etc. This crate offers [`type_union!`],
+//! which declares a named union with named selectors, for any number of members.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use syn::{token, Attribute, Ident, ItemUnion};
+
+/// Returns `true` if `attr` is `#[repr(C)]`, or a `repr` that includes `C`.
+fn is_repr_c(attr: &Attribute) -> bool {
+    let mut repr_c = false;
+
+    if attr.path().is_ident("repr") {
+        // A `repr` that can not be parsed is left to the compiler to reject.
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+
+            // Skips the arguments of hints like `align(8)`.
+            if meta.input.peek(token::Paren) {
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+
+            Ok(())
+        });
+    }
+
+    repr_c
+}
+
+/// Declares a named union, that can be used as the `U` of a `UnionVec` or a `SelectHandle`.
+///
+/// Every field of the union becomes a member, and for every field a zero-sized selector with the
+/// name of that field is generated. The selectors are used in the same way as `Type1`, `Type2`,
+/// etc. are for the tuple unions.
+///
+/// The union only implements `ReprC` when it is declared with `#[repr(C)]`, which is not added
+/// otherwise.
+///
+/// # Examples
+/// ```ignore
+/// type_union! {
+///     pub union Stage {
+///         Raw: String,
+///         Parsed: u64,
+///         Bytes: Vec<u8>
+///     }
+/// }
+///
+/// let v = UnionVec::<String, Stage>::new();
+/// let v = v.map::<Parsed, _>(|s| s.parse().unwrap());
+/// ```
+#[proc_macro]
+pub fn type_union(input: TokenStream) -> TokenStream {
+    let item: ItemUnion = syn::parse(input).expect("type_union! expects a union declaration");
+
+    if item.fields.named.is_empty() {
+        panic!("type_union! expects a union with at least one member");
+    }
+
+    let attrs = &item.attrs;
+    let vis = &item.vis;
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let selectors: Vec<&Ident> = item.fields
+        .named
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect();
+
+    let types: Vec<&syn::Type> = item.fields.named.iter().map(|field| &field.ty).collect();
+
+    let fields = selectors.iter().zip(types.iter()).map(|(selector, ty)| {
+        quote! { #selector: ::std::mem::ManuallyDrop<#ty> }
+    });
+
+    let selector_impls = selectors.iter().zip(types.iter()).enumerate();
+    let selector_impls = selector_impls.map(|(index, (selector, ty))| {
+        let doc = format!("Selects the `{}` member of [`{}`].", selector, name);
+
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+            #vis struct #selector;
+
+            impl ::unioncollections::select::Selector for #selector {
+                const INDEX: usize = #index;
+            }
+
+            impl #impl_generics ::unioncollections::select::Select<#selector> for #name #ty_generics
+            #where_clause
+            {
+                type Output = #ty;
+            }
+
+            impl #impl_generics ::unioncollections::select::Contains<#ty, #selector>
+                for #name #ty_generics
+            #where_clause
+            {}
+        }
+    });
+
+    let subset_params: Vec<Ident> = (0..selectors.len())
+        .map(|i| Ident::new(&format!("S{}", i), Span::call_site()))
+        .collect();
+
+    let mut subset_generics = item.generics.clone();
+    subset_generics.params.push(parse_quote!(__U));
+    for (param, ty) in subset_params.iter().zip(types.iter()) {
+        subset_generics.params.push(parse_quote!(#param));
+        subset_generics.make_where_clause().predicates.push(parse_quote! {
+            #param: ::unioncollections::select::Selector
+        });
+        subset_generics.make_where_clause().predicates.push(parse_quote! {
+            __U: ::unioncollections::select::Contains<#ty, #param>
+        });
+    }
+    let (subset_impl_generics, _, subset_where_clause) = subset_generics.split_for_impl();
+    let subset_selectors = subset_params.iter();
+
+    let t = Ident::new("T", Span::call_site());
+    let contains = types.iter().map(|ty| {
+        quote! { || ::unioncollections::type_id::<#t>() == ::unioncollections::type_id::<#ty>() }
+    });
+
+    let layout_types = types.iter();
+    let type_id_types = types.iter();
+    let type_name_types = types.iter();
+    let drop_indices = 0..types.len();
+    let drop_types = types.iter();
+    let visits = selectors.iter().zip(types.iter()).enumerate();
+    let visits = visits.map(|(index, (selector, ty))| {
+        quote! {
+            visitor.visit::<#selector>(::unioncollections::select::MemberInfo {
+                index: #index,
+                type_id: ::unioncollections::type_id::<#ty>(),
+                layout: ::std::alloc::Layout::new::<#ty>(),
+                name: ::std::any::type_name::<#ty>(),
+            });
+        }
+    });
+
+    let repr_c = if attrs.iter().any(is_repr_c) {
+        quote! {
+            unsafe impl #impl_generics ::unioncollections::select::ReprC for #name #ty_generics
+            #where_clause
+            {}
+        }
+    } else {
+        quote! {}
+    };
+
+    let expanded = quote! {
+        #(#attrs)*
+        #[allow(non_snake_case)]
+        #vis union #name #impl_generics #where_clause {
+            #(#fields,)*
+        }
+
+        impl #impl_generics ::unioncollections::select::TypeUnion for #name #ty_generics
+        #where_clause
+        {
+            type Union = Self;
+
+            const MEMBER_LAYOUTS: &'static [::std::alloc::Layout] = &[
+                #(::std::alloc::Layout::new::<#layout_types>(),)*
+            ];
+
+            #[inline]
+            fn contains<#t: 'static>() -> bool
+            where
+                Self: 'static,
+            {
+                false #(#contains)*
+            }
+
+            #[inline]
+            fn member_type_ids() -> Vec<::std::any::TypeId>
+            where
+                Self: 'static,
+            {
+                vec![#(::unioncollections::type_id::<#type_id_types>(),)*]
+            }
+
+            #[inline]
+            fn member_type_names() -> Vec<&'static str> {
+                vec![#(::std::any::type_name::<#type_name_types>(),)*]
+            }
+
+            #[inline]
+            unsafe fn drop_member(index: usize, union: *mut Self) {
+                match index {
+                    #(#drop_indices => ::std::ptr::drop_in_place(union as *mut #drop_types),)*
+                    _ => panic!("index out of range for {}", stringify!(#name)),
+                }
+            }
+
+            #[inline]
+            fn for_each_member<__V>(visitor: &mut __V)
+            where
+                __V: ::unioncollections::select::MemberVisitor<Self>,
+                Self: 'static,
+            {
+                #(#visits)*
+            }
+        }
+
+        unsafe impl #impl_generics ::unioncollections::select::TypeSelect<#name #ty_generics>
+            for #name #ty_generics
+        #where_clause
+        {}
+
+        #repr_c
+
+        impl #subset_impl_generics ::unioncollections::select::SubsetOf<__U, (#(#subset_selectors,)*)>
+            for #name #ty_generics
+        #subset_where_clause
+        {}
+
+        #(#selector_impls)*
+    };
+
+    expanded.into()
+}